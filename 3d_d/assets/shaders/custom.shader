@@ -0,0 +1,11 @@
+# Se recarga al guardar mientras el programa corre.
+# Entradas: x, y, z (dirección en la esfera), u, v y t (tiempo).
+# Salidas: r, g, b y opcionalmente a.
+
+let n = fbm(x * 3, y * 3, z * 3) * 0.5 + 0.5
+let bands = sin(y * 12 + n * 4 + t * 0.2) * 0.5 + 0.5
+let edge = smoothstep(0.1, 0.3, cells(x * 6, y * 6, z * 6))
+
+r = mix(0.35, 0.75, bands) * edge
+g = mix(0.15, 0.45, n) * edge
+b = mix(0.5, 0.9, bands * n)
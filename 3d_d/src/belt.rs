@@ -0,0 +1,129 @@
+use crate::body::Orbit;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+// Cómo se reparte un cinturón; de la misma configuración siempre salen las
+// mismas rocas
+#[derive(Clone, Debug)]
+pub struct BeltSettings {
+    pub shader: String,
+    pub seed: u64,
+    pub count: u32,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    // Inclinación máxima de cada órbita respecto al plano XY (radianes)
+    pub inclination: f32,
+    // Excentricidad máxima de cada órbita; 0 las deja circulares
+    pub eccentricity: f32,
+    // Escala media de cada roca
+    pub size: f32,
+    // Velocidad orbital en el borde interior; hacia afuera baja como r^-1.5
+    pub orbit_speed: f32,
+}
+
+impl Default for BeltSettings {
+    fn default() -> Self {
+        BeltSettings {
+            shader: "moon".to_string(),
+            seed: 0,
+            count: 300,
+            inner_radius: 100.0,
+            outer_radius: 150.0,
+            inclination: 0.05,
+            eccentricity: 0.0,
+            size: 4.0,
+            orbit_speed: 0.3,
+        }
+    }
+}
+
+// Una roca en órbita inclinada alrededor del centro del sistema
+#[derive(Clone, Debug)]
+pub struct Asteroid {
+    orbit: Orbit,
+    // Anomalía media inicial, para que no arranquen todas juntas
+    phase: f32,
+    pub scale: f32,
+    tumble: Vector3,
+    tumble_rate: Vector3,
+}
+
+impl Asteroid {
+    pub fn position(&self, angle: f32) -> Vector3 {
+        self.orbit.offset_with_phase(angle, self.phase)
+    }
+
+    // Cada roca gira sobre sí misma en los tres ejes a su propio ritmo
+    pub fn rotation(&self, angle: f32) -> Vector3 {
+        self.tumble + self.tumble_rate * angle
+    }
+}
+
+pub struct AsteroidBelt {
+    pub shader: String,
+    pub asteroids: Vec<Asteroid>,
+    // La configuración de la que salió, para poder guardarla
+    pub settings: BeltSettings,
+}
+
+impl AsteroidBelt {
+    pub fn generate(settings: &BeltSettings) -> Self {
+        let mut rng = StdRng::seed_from_u64(settings.seed);
+        let turn = 0.0..2.0 * PI;
+
+        let asteroids = (0..settings.count)
+            .map(|_| {
+                // La densidad se concentra hacia el centro del anillo
+                let t = (rng.gen_range(0.0..1.0) + rng.gen_range(0.0..1.0)) / 2.0;
+                let radius =
+                    settings.inner_radius + (settings.outer_radius - settings.inner_radius) * t;
+                let max_inclination = settings.inclination.max(f32::EPSILON);
+                let phase = rng.gen_range(turn.clone());
+                let speed = settings.orbit_speed * (settings.inner_radius / radius).powf(1.5);
+                let inclination = rng.gen_range(-max_inclination..max_inclination);
+                let ascending_node = rng.gen_range(turn.clone());
+                let scale = settings.size * rng.gen_range(0.4..1.6);
+                let tumble = Vector3::new(
+                    rng.gen_range(turn.clone()),
+                    rng.gen_range(turn.clone()),
+                    rng.gen_range(turn.clone()),
+                );
+                let tumble_rate = Vector3::new(
+                    rng.gen_range(-3.0..3.0),
+                    rng.gen_range(-3.0..3.0),
+                    rng.gen_range(-3.0..3.0),
+                );
+                // Solo se sortea si hace falta, así los cinturones circulares
+                // de siempre no cambian
+                let (eccentricity, periapsis) = if settings.eccentricity > 0.0 {
+                    let eccentricity = rng.gen_range(0.0..settings.eccentricity);
+                    (eccentricity, rng.gen_range(turn.clone()))
+                } else {
+                    (0.0, 0.0)
+                };
+                Asteroid {
+                    orbit: Orbit {
+                        semi_major_axis: radius,
+                        eccentricity,
+                        periapsis,
+                        inclination,
+                        ascending_node,
+                        speed,
+                    },
+                    phase,
+                    scale,
+                    tumble,
+                    tumble_rate,
+                }
+            })
+            .collect();
+
+        AsteroidBelt {
+            shader: settings.shader.clone(),
+            asteroids,
+            settings: settings.clone(),
+        }
+    }
+}
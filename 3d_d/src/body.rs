@@ -0,0 +1,243 @@
+use crate::shader_params::ShaderParams;
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+// Órbita kepleriana alrededor del padre, que está en uno de los focos de la
+// elipse. El plano de referencia es XY; la órbita se inclina alrededor de la
+// línea de nodos.
+#[derive(Clone, Copy, Debug)]
+pub struct Orbit {
+    pub semi_major_axis: f32,
+    // 0 = círculo; cerca de 1, una elipse muy alargada
+    pub eccentricity: f32,
+    // Ángulo del periapsis (el punto más cercano) medido desde el nodo
+    // ascendente, dentro del plano de la órbita
+    pub periapsis: f32,
+    // Inclinación del plano de la órbita respecto a XY (radianes)
+    pub inclination: f32,
+    // Dirección en XY donde la órbita sube por encima del plano
+    pub ascending_node: f32,
+    // Radianes de anomalía media por cada radián del ángulo de simulación
+    pub speed: f32,
+}
+
+impl Orbit {
+    pub fn offset(&self, angle: f32) -> Vector3 {
+        self.offset_with_phase(angle, 0.0)
+    }
+
+    // Como `offset`, con la anomalía media adelantada `phase` radianes
+    pub fn offset_with_phase(&self, angle: f32, phase: f32) -> Vector3 {
+        let mean_anomaly = phase + angle * self.speed;
+        self.point_at(eccentric_anomaly(mean_anomaly, self.eccentricity))
+    }
+
+    // La elipse completa como polilínea cerrada, relativa al padre
+    pub fn path(&self, segments: usize) -> Vec<Vector3> {
+        (0..=segments)
+            .map(|i| self.point_at(i as f32 / segments as f32 * 2.0 * PI))
+            .collect()
+    }
+
+    fn point_at(&self, anomaly: f32) -> Vector3 {
+        let e = self.eccentricity;
+        // Posición con el periapsis sobre +X; el padre está en el origen
+        let x = self.semi_major_axis * (anomaly.cos() - e);
+        let y = self.semi_major_axis * (1.0 - e * e).sqrt() * anomaly.sin();
+        // Periapsis dentro del plano, luego inclinación alrededor de X y por
+        // último el giro del nodo alrededor de Z
+        let (sin_w, cos_w) = self.periapsis.sin_cos();
+        let (x, y) = (x * cos_w - y * sin_w, x * sin_w + y * cos_w);
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+        let (y, z) = (y * cos_i, y * sin_i);
+        let (sin_n, cos_n) = self.ascending_node.sin_cos();
+        Vector3::new(x * cos_n - y * sin_n, x * sin_n + y * cos_n, z)
+    }
+}
+
+// Resuelve la ecuación de Kepler M = E - e·sin(E) con Newton. Como la
+// anomalía media avanza parejo, el cuerpo va más rápido cerca del periapsis.
+fn eccentric_anomaly(mean_anomaly: f32, eccentricity: f32) -> f32 {
+    let m = mean_anomaly.rem_euclid(2.0 * PI);
+    let mut anomaly = if eccentricity < 0.8 { m } else { PI };
+    for _ in 0..8 {
+        let error = anomaly - eccentricity * anomaly.sin() - m;
+        anomaly -= error / (1.0 - eccentricity * anomaly.cos());
+        if error.abs() < 1e-6 {
+            break;
+        }
+    }
+    anomaly
+}
+
+// Esfera translúcida concéntrica con el cuerpo (nubes, atmósfera)
+#[derive(Clone, Debug)]
+pub struct Shell {
+    pub shader: String,
+    // Relativa a la escala del cuerpo
+    pub scale: f32,
+}
+
+// Un cuerpo del sistema: los shaders se nombran como en el `ShaderRegistry` y
+// las lunas van como hijos, orbitando la posición animada de su planeta
+#[derive(Clone, Debug)]
+pub struct CelestialBody {
+    pub name: String,
+    pub shader: String,
+    pub params: ShaderParams,
+    pub seed: u64,
+    pub scale: f32,
+    // Sin órbita se queda en el centro de su padre (o del sistema)
+    pub orbit: Option<Orbit>,
+    // Inclinación del eje de giro respecto al plano de la órbita (radianes);
+    // el eje apunta siempre al mismo lado mientras el cuerpo orbita
+    pub axial_tilt: f32,
+    // Cuánto avanza el ángulo de simulación en una vuelta sobre su eje;
+    // 0 = no gira y negativo = retrógrado
+    pub rotation_period: f32,
+    pub shells: Vec<Shell>,
+    // Cola de partículas de cometa, opuesta al sol
+    pub tail: bool,
+    // Anillo en el plano ecuatorial
+    pub rings: bool,
+    // Color de la luz que emite (las estrellas); ilumina a todo el sistema
+    pub light: Option<Vector3>,
+    // Parámetro gravitacional (G·m) en el modo de gravedad; sin él se deduce
+    // de las órbitas que lo rodean
+    pub mass: Option<f32>,
+    pub children: Vec<CelestialBody>,
+}
+
+impl CelestialBody {
+    pub fn new(name: &str, shader: &str, scale: f32, seed: u64) -> Self {
+        CelestialBody {
+            name: name.to_string(),
+            shader: shader.to_string(),
+            params: ShaderParams::new(),
+            seed,
+            scale,
+            orbit: None,
+            axial_tilt: 0.0,
+            rotation_period: 0.0,
+            shells: Vec::new(),
+            tail: false,
+            rings: false,
+            light: None,
+            mass: None,
+            children: Vec::new(),
+        }
+    }
+
+    // Ángulo de giro sobre su eje (Y del cuerpo, antes de la inclinación)
+    pub fn spin(&self, angle: f32) -> f32 {
+        if self.rotation_period == 0.0 {
+            0.0
+        } else {
+            2.0 * PI * angle / self.rotation_period
+        }
+    }
+}
+
+// Radio de sphere.obj: el de un cuerpo es su escala por esto
+pub const SPHERE_RADIUS: f32 = 0.524;
+
+// Un cuerpo con su posición en el mundo para este frame
+pub struct PlacedBody<'a> {
+    pub body: &'a CelestialBody,
+    pub position: Vector3,
+}
+
+impl PlacedBody<'_> {
+    pub fn radius(&self) -> f32 {
+        self.body.scale * SPHERE_RADIUS
+    }
+}
+
+// El cuerpo número `index` en preorden (cada padre antes que sus lunas), el
+// mismo orden de `SceneNode::placed_bodies`; así el índice de un cuerpo es
+// estable mientras no cambie la escena
+pub fn body_mut(bodies: &mut [CelestialBody], index: usize) -> Option<&mut CelestialBody> {
+    let mut remaining = index;
+    find_mut(bodies, &mut remaining)
+}
+
+fn find_mut<'a>(
+    bodies: &'a mut [CelestialBody],
+    remaining: &mut usize,
+) -> Option<&'a mut CelestialBody> {
+    for body in bodies {
+        if *remaining == 0 {
+            return Some(body);
+        }
+        *remaining -= 1;
+        if let Some(found) = find_mut(&mut body.children, remaining) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+// El primer cuerpo con ese nombre, lunas incluidas
+pub fn body_by_name<'a>(
+    bodies: &'a mut [CelestialBody],
+    name: &str,
+) -> Option<&'a mut CelestialBody> {
+    for body in bodies {
+        if body.name == name {
+            return Some(body);
+        }
+        if let Some(found) = body_by_name(&mut body.children, name) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+// Saca el cuerpo número `index` en preorden, con sus lunas
+pub fn remove_body(bodies: &mut Vec<CelestialBody>, index: usize) -> Option<CelestialBody> {
+    let mut remaining = index;
+    remove_from(bodies, &mut remaining)
+}
+
+fn remove_from(bodies: &mut Vec<CelestialBody>, remaining: &mut usize) -> Option<CelestialBody> {
+    for i in 0..bodies.len() {
+        if *remaining == 0 {
+            return Some(bodies.remove(i));
+        }
+        *remaining -= 1;
+        if let Some(found) = remove_from(&mut bodies[i].children, remaining) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+// Saca solo el cuerpo número `index`: sus lunas quedan en su lugar, así el
+// preorden de todos los demás no cambia
+pub fn dissolve_body(bodies: &mut Vec<CelestialBody>, index: usize) -> Option<CelestialBody> {
+    let mut remaining = index;
+    dissolve_from(bodies, &mut remaining)
+}
+
+fn dissolve_from(bodies: &mut Vec<CelestialBody>, remaining: &mut usize) -> Option<CelestialBody> {
+    for i in 0..bodies.len() {
+        if *remaining == 0 {
+            let mut body = bodies.remove(i);
+            let moons = std::mem::take(&mut body.children);
+            bodies.splice(i..i, moons);
+            return Some(body);
+        }
+        *remaining -= 1;
+        if let Some(found) = dissolve_from(&mut bodies[i].children, remaining) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+pub fn count_bodies(bodies: &[CelestialBody]) -> usize {
+    bodies
+        .iter()
+        .map(|body| 1 + count_bodies(&body.children))
+        .sum()
+}
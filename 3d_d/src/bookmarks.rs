@@ -0,0 +1,74 @@
+use crate::camera::Camera;
+use std::fs;
+
+pub const SLOTS: usize = 9;
+
+// Cámaras guardadas con Ctrl+1..9. En disco es una línea por ranura con
+// posición, objetivo, `up` y apertura:
+//
+//     3 = [0, 0, 900, 0, 0, 0, 0, 1, 0, 0.785]
+pub struct Bookmarks {
+    slots: [Option<Camera>; SLOTS],
+}
+
+impl Bookmarks {
+    // Sin archivo no hay marcadores; las líneas que no se entienden se
+    // avisan y se saltan
+    pub fn load(path: &str) -> Self {
+        let mut bookmarks = Bookmarks {
+            slots: [None; SLOTS],
+        };
+        let Ok(source) = fs::read_to_string(path) else {
+            return bookmarks;
+        };
+        for (index, line) in source.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            match parse_line(line) {
+                Some((slot, camera)) => bookmarks.set(slot, camera),
+                None => eprintln!("⚠ {path}: línea {} inválida", index + 1),
+            }
+        }
+        bookmarks
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut source = String::from("# Cámaras guardadas con Ctrl+1..9\n");
+        for (index, camera) in self.slots.iter().enumerate() {
+            if let Some(camera) = camera {
+                let numbers: Vec<String> = camera
+                    .to_numbers()
+                    .iter()
+                    .map(|number| number.to_string())
+                    .collect();
+                source.push_str(&format!("{} = [{}]\n", index + 1, numbers.join(", ")));
+            }
+        }
+        fs::write(path, source).map_err(|e| format!("{path}: {e}"))
+    }
+
+    // Ranuras de 1 a 9, como las teclas
+    pub fn get(&self, slot: usize) -> Option<Camera> {
+        self.slots.get(slot.checked_sub(1)?).copied().flatten()
+    }
+
+    pub fn set(&mut self, slot: usize, camera: Camera) {
+        if let Some(entry) = slot.checked_sub(1).and_then(|i| self.slots.get_mut(i)) {
+            *entry = Some(camera);
+        }
+    }
+}
+
+fn parse_line(line: &str) -> Option<(usize, Camera)> {
+    let (slot, value) = line.split_once('=')?;
+    let slot: usize = slot.trim().parse().ok()?;
+    let value = value.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let numbers = value
+        .split(',')
+        .map(|number| number.trim().parse::<f32>().ok())
+        .collect::<Option<Vec<f32>>>()?;
+    let camera = Camera::from_numbers(&numbers)?;
+    (1..=SLOTS).contains(&slot).then_some((slot, camera))
+}
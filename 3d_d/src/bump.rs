@@ -0,0 +1,43 @@
+use raylib::prelude::*;
+
+// Paso de las diferencias finitas, en unidades de la esfera unitaria
+const EPSILON: f32 = 1e-3;
+
+// Normal perturbada por un relieve procedural, en espacio de objeto.
+// `height` recibe una dirección unitaria desde el centro del modelo; la
+// pendiente se mide sobre dos tangentes y se inclina la normal en contra.
+pub fn perturbed_normal(dir: Vector3, strength: f32, height: impl Fn(Vector3) -> f32) -> Vector3 {
+    let normal = dir.normalized();
+    let (tangent, bitangent) = tangent_basis(normal);
+
+    let h = height(normal);
+    let slope_t = (height((normal + tangent * EPSILON).normalized()) - h) / EPSILON;
+    let slope_b = (height((normal + bitangent * EPSILON).normalized()) - h) / EPSILON;
+
+    (normal - (tangent * slope_t + bitangent * slope_b) * strength).normalized()
+}
+
+// Dos tangentes perpendiculares a la normal; el eje auxiliar cambia cerca de
+// los polos para que el producto cruz no se anule
+fn tangent_basis(normal: Vector3) -> (Vector3, Vector3) {
+    let helper = if normal.y.abs() < 0.99 {
+        Vector3::new(0.0, 1.0, 0.0)
+    } else {
+        Vector3::new(1.0, 0.0, 0.0)
+    };
+    let tangent = helper.cross(normal).normalized();
+    let bitangent = normal.cross(tangent);
+    (tangent, bitangent)
+}
+
+// Lleva una normal de objeto a mundo con la parte de rotación del modelo
+// (sirve mientras la escala sea uniforme)
+pub fn to_world(model_matrix: &Matrix, normal: Vector3) -> Vector3 {
+    let m = model_matrix;
+    Vector3::new(
+        m.m0 * normal.x + m.m4 * normal.y + m.m8 * normal.z,
+        m.m1 * normal.x + m.m5 * normal.y + m.m9 * normal.z,
+        m.m2 * normal.x + m.m6 * normal.y + m.m10 * normal.z,
+    )
+    .normalized()
+}
@@ -0,0 +1,400 @@
+use crate::matrix::new_matrix4;
+use crate::viewport::Viewport;
+use raylib::prelude::*;
+use std::f32::consts::FRAC_PI_2;
+
+// Cuánto puede subir o bajar la cámara al orbitar sin llegar al polo, donde
+// `up` y la dirección de vista coinciden
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+// Al enfocar un cuerpo, la distancia en radios del cuerpo
+const FRAME_RADII: f32 = 6.0;
+// Dónde arranca la cámara de persecución, en radios del cuerpo: un poco
+// hacia afuera de la estrella, detrás y por encima del plano de la órbita
+const CHASE_OFFSET: Vector3 = Vector3::new(2.0, -6.0, 3.0);
+// Qué tan rápido la cámara que se ve alcanza a la de los controles: en
+// 1/SHARPNESS segundos recorre ~63% de lo que le falta
+const SHARPNESS: f32 = 8.0;
+
+// Alrededor de qué gira la cámara con las flechas
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraMode {
+    // El centro del sistema
+    System,
+    // El cuerpo seleccionado, al que sigue en su órbita
+    Body,
+    // Vuelo libre: la cámara se mueve y `target` va delante de ella
+    Free,
+    // Persecución: acompaña al cuerpo seleccionado girando con su órbita
+    Follow,
+}
+
+impl CameraMode {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "system" => Some(CameraMode::System),
+            "body" => Some(CameraMode::Body),
+            "free" => Some(CameraMode::Free),
+            "follow" => Some(CameraMode::Follow),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CameraMode::System => "system",
+            CameraMode::Body => "body",
+            CameraMode::Free => "free",
+            CameraMode::Follow => "follow",
+        }
+    }
+}
+
+// Posición de la cámara de persecución en los ejes de la órbita del cuerpo:
+// x hacia afuera de la estrella, y hacia adelante y z normal al plano XY. Así
+// se lo ve siempre del mismo lado respecto a su estrella mientras orbita.
+pub struct Chase {
+    pub offset: Vector3,
+}
+
+impl Chase {
+    pub fn new(radius: f32) -> Self {
+        Chase {
+            offset: CHASE_OFFSET * radius,
+        }
+    }
+
+    // Gira el desplazamiento alrededor de la normal y lo inclina hacia ella
+    pub fn turn(&mut self, yaw: f32, pitch: f32) {
+        let o = self.offset;
+        let (_, elevation, distance) = angles(Vector3::new(o.x, o.z, o.y));
+        let azimuth = o.y.atan2(o.x) + yaw;
+        let elevation = (elevation + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+        let (sin, cos) = azimuth.sin_cos();
+        self.offset = Vector3::new(
+            cos * elevation.cos(),
+            sin * elevation.cos(),
+            elevation.sin(),
+        ) * distance;
+    }
+
+    pub fn zoom(&mut self, factor: f32) {
+        self.offset *= factor;
+    }
+
+    // Ubica la cámara detrás de `target`, con la estrella en `star`
+    pub fn place(&self, camera: &mut Camera, target: Vector3, star: Vector3) {
+        let normal = Vector3::new(0.0, 0.0, 1.0);
+        let radial = target - star;
+        let radial = Vector3::new(radial.x, radial.y, 0.0);
+        let outward = if radial.dot(radial) > 1e-6 {
+            radial.normalized()
+        } else {
+            Vector3::new(1.0, 0.0, 0.0)
+        };
+        let along = normal.cross(outward);
+        camera.target = target;
+        camera.position =
+            target + outward * self.offset.x + along * self.offset.y + normal * self.offset.z;
+        camera.up = normal;
+    }
+}
+
+// Cámara en perspectiva: dónde está, a qué mira y con qué lente. De acá salen
+// las matrices de vista y proyección; los controles solo la mueven.
+#[derive(Clone, Copy, Debug)]
+pub struct Camera {
+    pub position: Vector3,
+    pub target: Vector3,
+    pub up: Vector3,
+    // Apertura vertical (radianes)
+    pub fov_y: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Camera {
+    pub fn new(position: Vector3, target: Vector3, fov_y: f32) -> Self {
+        Camera {
+            position,
+            target,
+            up: Vector3::up(),
+            fov_y,
+            near: 0.1,
+            far: 5000.0,
+        }
+    }
+
+    pub fn with_up(mut self, up: Vector3) -> Self {
+        self.up = up;
+        self
+    }
+
+    // Posición, objetivo, `up` y apertura en una lista, como se guarda en disco
+    pub fn to_numbers(self) -> [f32; 10] {
+        let [p, t, u] = [self.position, self.target, self.up];
+        [p.x, p.y, p.z, t.x, t.y, t.z, u.x, u.y, u.z, self.fov_y]
+    }
+
+    pub fn from_numbers(numbers: &[f32]) -> Option<Self> {
+        let [px, py, pz, tx, ty, tz, ux, uy, uz, fov_y] = numbers[..] else {
+            return None;
+        };
+        let camera = Camera::new(Vector3::new(px, py, pz), Vector3::new(tx, ty, tz), fov_y);
+        Some(camera.with_up(Vector3::new(ux, uy, uz)))
+    }
+
+    // Yaw, pitch y distancia respecto a `target`
+    pub fn orbit_angles(&self) -> (f32, f32, f32) {
+        angles(self.position - self.target)
+    }
+
+    pub fn set_orbit(&mut self, yaw: f32, pitch: f32, distance: f32) {
+        self.position = self.target + direction(yaw, pitch) * distance;
+    }
+
+    // Gira la mirada sin moverse: `target` da vueltas alrededor de la cámara
+    pub fn look(&mut self, yaw: f32, pitch: f32) {
+        let (current_yaw, current_pitch, distance) = angles(self.target - self.position);
+        self.target =
+            self.position + direction(current_yaw + yaw, current_pitch + pitch) * distance;
+    }
+
+    // Traslada la cámara y lo que mira, en sus propios ejes
+    pub fn fly(&mut self, right: f32, up: f32, forward: f32) {
+        let ahead = self.forward();
+        let side = ahead.cross(self.up).normalized();
+        let step = side * right + side.cross(ahead) * up + ahead * forward;
+        self.position += step;
+        self.target += step;
+    }
+
+    // Gira alrededor de `target` sin cambiar la distancia
+    pub fn orbit(&mut self, yaw: f32, pitch: f32) {
+        let (current_yaw, current_pitch, distance) = self.orbit_angles();
+        self.set_orbit(current_yaw + yaw, current_pitch + pitch, distance);
+    }
+
+    // Lleva `target` a otro punto con la misma orientación y distancia
+    pub fn retarget(&mut self, target: Vector3) {
+        let (yaw, pitch, distance) = self.orbit_angles();
+        self.target = target;
+        self.set_orbit(yaw, pitch, distance);
+    }
+
+    // Mira a un cuerpo de ese radio desde cerca, sin cambiar la orientación
+    pub fn frame(&mut self, target: Vector3, radius: f32) {
+        let (yaw, pitch, _) = self.orbit_angles();
+        self.target = target;
+        self.set_orbit(yaw, pitch, radius * FRAME_RADII);
+    }
+
+    // Acerca (< 1) o aleja (> 1) la cámara de `target`
+    pub fn zoom(&mut self, factor: f32) {
+        self.position = self.target + (self.position - self.target) * factor;
+    }
+
+    pub fn forward(&self) -> Vector3 {
+        (self.target - self.position).normalized()
+    }
+
+    // Píxeles por unidad de mundo a distancia 1 en un viewport de esa altura
+    pub fn focal_length(&self, viewport_height: f32) -> f32 {
+        viewport_height / 2.0 / (self.fov_y / 2.0).tan()
+    }
+
+    pub fn view_matrix(&self) -> Matrix {
+        let forward = self.forward();
+        let right = forward.cross(self.up).normalized();
+        let camera_up = right.cross(forward);
+        let eye = self.position;
+
+        new_matrix4(
+            right.x,
+            right.y,
+            right.z,
+            -right.dot(eye),
+            camera_up.x,
+            camera_up.y,
+            camera_up.z,
+            -camera_up.dot(eye),
+            -forward.x,
+            -forward.y,
+            -forward.z,
+            forward.dot(eye),
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        )
+    }
+
+    pub fn projection_matrix(&self, aspect: f32) -> Matrix {
+        let f = 1.0 / (self.fov_y / 2.0).tan();
+        let (near, far) = (self.near, self.far);
+
+        new_matrix4(
+            f / aspect,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            f,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            (far + near) / (near - far),
+            (2.0 * far * near) / (near - far),
+            0.0,
+            0.0,
+            -1.0,
+            0.0,
+        )
+    }
+
+    // Dirección del rayo que sale de la cámara por el píxel `pixel`
+    pub fn ray(&self, pixel: Vector2, viewport: &Viewport) -> Vector3 {
+        let forward = self.forward();
+        let right = forward.cross(self.up).normalized();
+        let up = right.cross(forward);
+        let half_height = (self.fov_y / 2.0).tan();
+        let x = 2.0 * (pixel.x - viewport.x as f32) / viewport.width as f32 - 1.0;
+        let y = 1.0 - 2.0 * (pixel.y - viewport.y as f32) / viewport.height as f32;
+        forward + right * (x * half_height * viewport.aspect()) + up * (y * half_height)
+    }
+}
+
+// Yaw alrededor de Y (0 mira hacia +Z), pitch hacia +Y y largo de `v`
+fn angles(v: Vector3) -> (f32, f32, f32) {
+    let length = v.length();
+    if length == 0.0 {
+        return (0.0, 0.0, 0.0);
+    }
+    let pitch = (v.y / length).clamp(-1.0, 1.0).asin();
+    (v.x.atan2(v.z), pitch, length)
+}
+
+fn direction(yaw: f32, pitch: f32) -> Vector3 {
+    let pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+    Vector3::new(
+        pitch.cos() * yaw.sin(),
+        pitch.sin(),
+        pitch.cos() * yaw.cos(),
+    )
+}
+
+// Paso suave de una cámara a otra en `duration` segundos, con arranque y
+// frenada graduales
+pub struct Transition {
+    from: Camera,
+    to: Camera,
+    elapsed: f32,
+    duration: f32,
+}
+
+impl Transition {
+    pub fn new(from: Camera, to: Camera, duration: f32) -> Self {
+        Transition {
+            from,
+            to,
+            elapsed: 0.0,
+            duration,
+        }
+    }
+
+    pub fn advance(&mut self, seconds: f32) -> Camera {
+        self.elapsed += seconds;
+        let t = (self.elapsed / self.duration).clamp(0.0, 1.0);
+        let t = t * t * (3.0 - 2.0 * t);
+        let (from, to) = (self.from, self.to);
+        // Con `up` opuestos la mezcla pasa por cero; ahí se toma el de llegada
+        let up = from.up.lerp(to.up, t);
+        Camera {
+            position: from.position.lerp(to.position, t),
+            target: from.target.lerp(to.target, t),
+            up: if up.dot(up) > 1e-6 {
+                up.normalized()
+            } else {
+                to.up
+            },
+            fov_y: from.fov_y + (to.fov_y - from.fov_y) * t,
+            ..to
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+// La cámara que se ve: persigue a la que mueven los controles con
+// amortiguación exponencial, igual a cualquier FPS. Los giros se suavizan
+// alrededor del punto fijo de cada modo (el objetivo al orbitar, el ojo al
+// volar) para no cortar camino por dentro.
+pub struct SmoothCamera {
+    pub camera: Camera,
+    // Objetivo de la otra cámara en el frame anterior
+    goal_target: Vector3,
+}
+
+impl SmoothCamera {
+    pub fn new(camera: Camera) -> Self {
+        SmoothCamera {
+            camera,
+            goal_target: camera.target,
+        }
+    }
+
+    pub fn follow(&mut self, goal: &Camera, mode: CameraMode, seconds: f32) {
+        let k = 1.0 - (-SHARPNESS * seconds).exp();
+        let camera = &mut self.camera;
+        if mode == CameraMode::Free {
+            let look = blend_offsets(
+                camera.target - camera.position,
+                goal.target - goal.position,
+                k,
+            );
+            camera.position = camera.position.lerp(goal.position, k);
+            camera.target = camera.position + look;
+        } else {
+            // El retraso se mide contra el objetivo anterior: si el objetivo
+            // es un cuerpo que se mueve, la cámara lo acompaña sin quedarse
+            // atrás y solo se suavizan los saltos
+            let lag = (camera.target - self.goal_target) * (1.0 - k);
+            let offset = blend_offsets(
+                camera.position - camera.target,
+                goal.position - goal.target,
+                k,
+            );
+            camera.target = goal.target + lag;
+            camera.position = camera.target + offset;
+        }
+        camera.up = blend_directions(camera.up, goal.up, k);
+        camera.fov_y += (goal.fov_y - camera.fov_y) * k;
+        self.goal_target = goal.target;
+    }
+}
+
+// Dirección por el arco más corto y largo en escala logarítmica, así acercarse
+// y alejarse se sienten parejos a cualquier distancia
+fn blend_offsets(from: Vector3, to: Vector3, k: f32) -> Vector3 {
+    let (from_length, to_length) = (from.length(), to.length());
+    if from_length < 1e-6 || to_length < 1e-6 {
+        return to;
+    }
+    let length = from_length * (to_length / from_length).powf(k);
+    blend_directions(from.normalized(), to.normalized(), k) * length
+}
+
+fn blend_directions(from: Vector3, to: Vector3, k: f32) -> Vector3 {
+    let angle = from.dot(to).clamp(-1.0, 1.0).acos();
+    let sin = angle.sin();
+    if angle < 1e-3 {
+        from.lerp(to, k).normalized()
+    } else if sin < 1e-3 {
+        // Opuestas: no hay un arco preferido
+        to
+    } else {
+        (from * ((1.0 - k) * angle).sin() + to * (k * angle).sin()) * (1.0 / sin)
+    }
+}
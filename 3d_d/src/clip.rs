@@ -0,0 +1,140 @@
+use crate::vertex_output::VertexOutput;
+use raylib::prelude::*;
+
+#[derive(Clone, Copy)]
+enum ClipPlane {
+    Left,
+    Right,
+    Bottom,
+    Top,
+    Near,
+    Far,
+}
+
+const FRUSTUM_PLANES: [ClipPlane; 6] = [
+    ClipPlane::Near,
+    ClipPlane::Far,
+    ClipPlane::Left,
+    ClipPlane::Right,
+    ClipPlane::Bottom,
+    ClipPlane::Top,
+];
+
+// Los triángulos que caen dentro de esta banda (en múltiplos de w) no se
+// recortan contra los planos laterales: el rasterizador ya limita su
+// recorrido al viewport. Solo los que la exceden se recortan
+const GUARD_BAND: f32 = 4.0;
+
+impl ClipPlane {
+    // Distancia con signo en clip space; el vértice está dentro si es >= 0
+    fn distance(self, vertex: &VertexOutput) -> f32 {
+        let p = vertex.clip_position;
+        match self {
+            ClipPlane::Left => p.w + p.x,
+            ClipPlane::Right => p.w - p.x,
+            ClipPlane::Bottom => p.w + p.y,
+            ClipPlane::Top => p.w - p.y,
+            ClipPlane::Near => p.w + p.z,
+            ClipPlane::Far => p.w - p.z,
+        }
+    }
+
+    // Como `distance`, pero con los planos laterales desplazados a la banda de guarda
+    fn guard_band_distance(self, vertex: &VertexOutput) -> f32 {
+        let p = vertex.clip_position;
+        match self {
+            ClipPlane::Left => GUARD_BAND * p.w + p.x,
+            ClipPlane::Right => GUARD_BAND * p.w - p.x,
+            ClipPlane::Bottom => GUARD_BAND * p.w + p.y,
+            ClipPlane::Top => GUARD_BAND * p.w - p.y,
+            ClipPlane::Near | ClipPlane::Far => self.distance(vertex),
+        }
+    }
+}
+
+// Recorte Sutherland–Hodgman en clip space contra los seis planos del
+// frustum, antes de la división de perspectiva. El polígono resultante se
+// vuelve a triangular en abanico conservando el orden de los vértices.
+pub fn clip_triangle_against_frustum(
+    v0: &VertexOutput,
+    v1: &VertexOutput,
+    v2: &VertexOutput,
+) -> Vec<[VertexOutput; 3]> {
+    let vertices = [v0, v1, v2];
+
+    // Rechazo trivial: los tres vértices fuera del mismo plano
+    if FRUSTUM_PLANES
+        .iter()
+        .any(|plane| vertices.iter().all(|v| plane.distance(v) < 0.0))
+    {
+        return Vec::new();
+    }
+
+    let mut polygon = vec![v0.clone(), v1.clone(), v2.clone()];
+    for plane in FRUSTUM_PLANES {
+        if vertices.iter().all(|v| plane.guard_band_distance(v) >= 0.0) {
+            continue;
+        }
+        polygon = clip_polygon(&polygon, plane);
+        if polygon.is_empty() {
+            return Vec::new();
+        }
+    }
+
+    let mut triangles = Vec::new();
+    for i in 1..polygon.len().saturating_sub(1) {
+        triangles.push([
+            polygon[0].clone(),
+            polygon[i].clone(),
+            polygon[i + 1].clone(),
+        ]);
+    }
+    triangles
+}
+
+fn clip_polygon(polygon: &[VertexOutput], plane: ClipPlane) -> Vec<VertexOutput> {
+    let mut output = Vec::new();
+
+    for i in 0..polygon.len() {
+        let current = &polygon[i];
+        let next = &polygon[(i + 1) % polygon.len()];
+        let d_current = plane.distance(current);
+        let d_next = plane.distance(next);
+
+        if d_current >= 0.0 {
+            output.push(current.clone());
+        }
+        if (d_current >= 0.0) != (d_next >= 0.0) {
+            let t = d_current / (d_current - d_next);
+            output.push(current.lerp(next, t));
+        }
+    }
+
+    output
+}
+
+// Prueba una esfera en espacio de mundo contra los planos del frustum,
+// extraídos de las filas de la matriz vista-proyección (Gribb–Hartmann)
+pub fn sphere_in_frustum(view_projection: &Matrix, center: Vector3, radius: f32) -> bool {
+    let m = view_projection;
+    let rows = [
+        Vector4::new(m.m0, m.m4, m.m8, m.m12),
+        Vector4::new(m.m1, m.m5, m.m9, m.m13),
+        Vector4::new(m.m2, m.m6, m.m10, m.m14),
+    ];
+    let w_row = Vector4::new(m.m3, m.m7, m.m11, m.m15);
+
+    rows.iter().all(|row| {
+        [1.0, -1.0].iter().all(|&sign| {
+            let plane = Vector4::new(
+                w_row.x + sign * row.x,
+                w_row.y + sign * row.y,
+                w_row.z + sign * row.z,
+                w_row.w + sign * row.w,
+            );
+            let normal = Vector3::new(plane.x, plane.y, plane.z);
+            let distance = normal.dot(center) + plane.w;
+            distance >= -radius * normal.length()
+        })
+    })
+}
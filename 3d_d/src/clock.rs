@@ -0,0 +1,64 @@
+use std::f32::consts::FRAC_PI_6;
+
+// A velocidad x1 el sistema avanza tanto como antes, cuando el ángulo de
+// órbita sumaba medio grado por frame a ~60 FPS (30° por segundo)
+pub const RADIANS_PER_SECOND: f32 = FRAC_PI_6;
+const MIN_SCALE: f32 = 0.1;
+const MAX_SCALE: f32 = 1000.0;
+
+// Tiempo de simulación: el ángulo con el que avanzan órbitas y rotaciones,
+// separado del tiempo real para poder pausarlo, frenarlo o acelerarlo
+pub struct SimulationClock {
+    pub time: f32,
+    pub scale: f32,
+    pub paused: bool,
+}
+
+impl SimulationClock {
+    pub fn new() -> Self {
+        SimulationClock {
+            time: 0.0,
+            scale: 1.0,
+            paused: false,
+        }
+    }
+
+    // Avanza con el tiempo real del frame y devuelve cuánto tiempo escalado
+    // pasó (0 en pausa)
+    pub fn advance(&mut self, real_dt: f32) -> f32 {
+        if self.paused {
+            return 0.0;
+        }
+        let dt = real_dt * self.scale;
+        self.time += dt * RADIANS_PER_SECOND;
+        dt
+    }
+
+    // Segundos de simulación, para lo que los shaders animan: se detiene en
+    // pausa y sigue la escala como las órbitas
+    pub fn seconds(&self) -> f32 {
+        self.time / RADIANS_PER_SECOND
+    }
+
+    pub fn faster(&mut self) {
+        self.set_scale(self.scale * 2.0);
+    }
+
+    pub fn slower(&mut self) {
+        self.set_scale(self.scale / 2.0);
+    }
+
+    // Escala pedida desde fuera (eventos, partidas guardadas), dentro de los
+    // mismos límites que el teclado: nunca detiene ni invierte el tiempo
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.clamp(MIN_SCALE, MAX_SCALE);
+    }
+
+    pub fn label(&self) -> String {
+        if self.paused {
+            format!("tiempo: x{} (pausa)", self.scale)
+        } else {
+            format!("tiempo: x{}", self.scale)
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use raylib::prelude::*;
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Interpolation {
+    Linear,
+    Smooth,
+}
+
+// Paleta definida por paradas (posición, color). Dos paradas en la misma
+// posición producen un corte brusco, como una línea de costa.
+#[derive(Clone, Debug)]
+pub struct ColorRamp {
+    stops: Vec<(f32, Vector3)>,
+    interpolation: Interpolation,
+}
+
+impl ColorRamp {
+    pub fn new(interpolation: Interpolation) -> Self {
+        ColorRamp {
+            stops: Vec::new(),
+            interpolation,
+        }
+    }
+
+    // Las paradas se mantienen ordenadas; con posiciones iguales queda
+    // después la que se agregó última
+    pub fn with_stop(mut self, position: f32, color: Vector3) -> Self {
+        let index = self.stops.partition_point(|(p, _)| *p <= position);
+        self.stops.insert(index, (position, color));
+        self
+    }
+
+    pub fn sample(&self, t: f32) -> Vector3 {
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return Vector3::zero();
+        };
+        if t < first.0 {
+            return first.1;
+        }
+        if t >= last.0 {
+            return last.1;
+        }
+
+        // Primer tramo que termina después de t
+        let next = self.stops.partition_point(|(p, _)| *p <= t);
+        let (start, from) = self.stops[next - 1];
+        let (end, to) = self.stops[next];
+        let mut amount = (t - start) / (end - start);
+        if self.interpolation == Interpolation::Smooth {
+            amount = amount * amount * (3.0 - 2.0 * amount);
+        }
+        from.lerp(to, amount)
+    }
+}
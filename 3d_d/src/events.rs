@@ -0,0 +1,192 @@
+use crate::body::{CelestialBody, Orbit, body_by_name};
+use crate::clock::SimulationClock;
+use crate::generator::kepler_speed;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f32::consts::PI;
+
+// Lo que un evento puede tocar de la simulación. Los cuerpos nuevos van al
+// final de la lista, como los que se agregan con B.
+pub struct World<'a> {
+    pub bodies: &'a mut Vec<CelestialBody>,
+    pub clock: &'a mut SimulationClock,
+}
+
+type Action = Box<dyn FnMut(&mut World)>;
+
+struct Hook {
+    // Próxima vez que corre, en tiempo de simulación
+    next: f32,
+    every: Option<f32>,
+    action: Action,
+}
+
+// Ganchos que corren en cada paso de la simulación cuando el reloj pasa por
+// su tiempo: una sola vez, o cada `every` si se repiten. Sirven para armar
+// demos coreografiadas.
+pub struct EventRegistry {
+    hooks: Vec<Hook>,
+}
+
+impl EventRegistry {
+    pub fn new() -> Self {
+        EventRegistry { hooks: Vec::new() }
+    }
+
+    pub fn with_event(
+        mut self,
+        time: f32,
+        every: Option<f32>,
+        action: impl FnMut(&mut World) + 'static,
+    ) -> Self {
+        self.hooks.push(Hook {
+            next: time,
+            every: every.filter(|every| *every > 0.0),
+            action: Box::new(action),
+        });
+        self
+    }
+
+    // Los eventos de una escena, en el orden en que aparecen
+    pub fn from_settings(settings: &[EventSettings]) -> Self {
+        settings
+            .iter()
+            .fold(EventRegistry::new(), |registry, event| {
+                registry.with_event(event.time, event.every, event.action())
+            })
+    }
+
+    // Corre lo que cae entre `previous` (incluido) y `now`. Si el reloj va
+    // hacia atrás (al cargar una partida) no corre nada.
+    pub fn run(&mut self, world: &mut World, previous: f32, now: f32) {
+        for hook in &mut self.hooks {
+            // Los que se repiten siguen desde su próxima vez después de
+            // `previous`, sin correr las que se saltearon
+            if let Some(every) = hook.every
+                && hook.next < previous
+            {
+                hook.next += ((previous - hook.next) / every).ceil() * every;
+            }
+            while hook.next >= previous && hook.next < now {
+                (hook.action)(world);
+                hook.next = match hook.every {
+                    Some(every) => hook.next + every,
+                    None => f32::INFINITY,
+                };
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EventAction {
+    // Cambia la velocidad del reloj a `value`
+    TimeScale,
+    Pause,
+    // Lleva la órbita de `body` a `value` de semieje mayor, con el período
+    // que le corresponde por Kepler. Con la gravedad activa no tiene efecto.
+    OrbitRadius,
+    // Suelta un cometa muy excéntrico de `value` de semieje mayor
+    Comet,
+}
+
+impl EventAction {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "time_scale" => Some(EventAction::TimeScale),
+            "pause" => Some(EventAction::Pause),
+            "orbit_radius" => Some(EventAction::OrbitRadius),
+            "comet" => Some(EventAction::Comet),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EventAction::TimeScale => "time_scale",
+            EventAction::Pause => "pause",
+            EventAction::OrbitRadius => "orbit_radius",
+            EventAction::Comet => "comet",
+        }
+    }
+}
+
+// Un evento escrito en la escena como `[[event]]`
+#[derive(Clone, Debug)]
+pub struct EventSettings {
+    pub time: f32,
+    pub every: Option<f32>,
+    pub action: EventAction,
+    pub body: String,
+    pub value: f32,
+}
+
+impl Default for EventSettings {
+    fn default() -> Self {
+        EventSettings {
+            time: 0.0,
+            every: None,
+            action: EventAction::TimeScale,
+            body: String::new(),
+            value: 1.0,
+        }
+    }
+}
+
+impl EventSettings {
+    fn action(&self) -> impl FnMut(&mut World) + 'static {
+        let EventSettings {
+            action,
+            body,
+            value,
+            time,
+            ..
+        } = self.clone();
+        let mut fired = 0;
+        move |world: &mut World| {
+            fired += 1;
+            match action {
+                EventAction::TimeScale => world.clock.set_scale(value),
+                EventAction::Pause => world.clock.paused = true,
+                // Un semieje nulo o negativo daría posiciones infinitas
+                EventAction::OrbitRadius | EventAction::Comet if value <= 0.0 => {
+                    eprintln!("⚠ Evento '{}': el radio debe ser positivo", action.name());
+                }
+                EventAction::OrbitRadius => {
+                    let orbit =
+                        body_by_name(world.bodies, &body).and_then(|body| body.orbit.as_mut());
+                    match orbit {
+                        Some(orbit) => {
+                            orbit.speed *= (orbit.semi_major_axis / value).powf(1.5);
+                            orbit.semi_major_axis = value;
+                        }
+                        None => eprintln!("⚠ Evento: no hay una órbita de '{body}'"),
+                    }
+                }
+                EventAction::Comet => {
+                    let name = if body.is_empty() { "Cometa" } else { &body };
+                    let seed = time.to_bits() as u64 + fired;
+                    world
+                        .bodies
+                        .push(comet(&format!("{name} {fired}"), value, seed));
+                }
+            }
+        }
+    }
+}
+
+fn comet(name: &str, semi_major_axis: f32, seed: u64) -> CelestialBody {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut body = CelestialBody::new(name, "ice", rng.gen_range(4.0..7.0), seed);
+    body.orbit = Some(Orbit {
+        semi_major_axis,
+        eccentricity: rng.gen_range(0.7..0.85),
+        periapsis: rng.gen_range(0.0..2.0 * PI),
+        inclination: rng.gen_range(0.1..0.6),
+        ascending_node: rng.gen_range(0.0..2.0 * PI),
+        speed: kepler_speed(semi_major_axis),
+    });
+    body.rotation_period = 2.0;
+    body.tail = true;
+    body
+}
@@ -1,12 +1,20 @@
 //fragment.rs
 #![allow(dead_code)]
 
+use crate::vertex_output::MAX_CUSTOM_VARYINGS;
 use raylib::math::{Vector2, Vector3};
 
 pub struct Fragment {
     pub position: Vector2,
     pub color: Vector3,
     pub depth: f32,
+    pub normal: Vector3,
+    pub tex_coords: Vector2,
+    pub world_position: Vector3,
+    pub object_position: Vector3,
+    pub custom: [f32; MAX_CUSTOM_VARYINGS],
+    // Máscara de muestras MSAA cubiertas (bit i = muestra i)
+    pub coverage: u8,
 }
 
 impl Fragment {
@@ -15,6 +23,12 @@ impl Fragment {
             position: Vector2::new(x, y),
             color,
             depth,
+            normal: Vector3::zero(),
+            tex_coords: Vector2::zero(),
+            world_position: Vector3::zero(),
+            object_position: Vector3::zero(),
+            custom: [0.0; MAX_CUSTOM_VARYINGS],
+            coverage: u8::MAX,
         }
     }
 }
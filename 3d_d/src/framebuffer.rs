@@ -1,10 +1,109 @@
+use crate::triangle::ScreenRect;
+use crate::viewport::Viewport;
 use raylib::prelude::*;
 
+// Comparación entre la profundidad del fragmento y la guardada en el buffer
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DepthFunc {
+    Less,
+    LessEqual,
+    Equal,
+    Always,
+    Greater,
+}
+
+impl DepthFunc {
+    pub fn passes(self, depth: f32, stored: f32) -> bool {
+        match self {
+            DepthFunc::Less => depth < stored,
+            DepthFunc::LessEqual => depth <= stored,
+            DepthFunc::Equal => depth == stored,
+            DepthFunc::Always => true,
+            DepthFunc::Greater => depth > stored,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StencilFunc {
+    Always,
+    NotEqual,
+}
+
+// Qué hacer con el valor del stencil cuando el fragmento se escribe
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StencilOp {
+    Keep,
+    Replace,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StencilState {
+    pub func: StencilFunc,
+    pub reference: u8,
+    pub pass_op: StencilOp,
+}
+
+impl Default for StencilState {
+    fn default() -> Self {
+        StencilState {
+            func: StencilFunc::Always,
+            reference: 0,
+            pass_op: StencilOp::Keep,
+        }
+    }
+}
+
+impl StencilState {
+    fn passes(&self, stored: u8) -> bool {
+        match self.func {
+            StencilFunc::Always => true,
+            StencilFunc::NotEqual => stored != self.reference,
+        }
+    }
+
+    fn apply(&self, stored: u8) -> u8 {
+        match self.pass_op {
+            StencilOp::Keep => stored,
+            StencilOp::Replace => self.reference,
+        }
+    }
+}
+
+// Matriz de Bayer 4x4: reparte los umbrales para que el error de cuantizar
+// forme un patrón fino en vez de bandas
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
 pub struct Framebuffer {
     pub width: u32,
     pub height: u32,
+    // Tamaño de la ventana; con SSAA se renderiza a `ssaa_factor` veces esto
+    output_width: u32,
+    output_height: u32,
+    ssaa_factor: u32,
     image: Image,
+    depth_buffer: Vec<f32>,
+    // Un valor de stencil por píxel (también con MSAA)
+    stencil_buffer: Vec<u8>,
+    stencil: StencilState,
+    // Con MSAA cada píxel guarda varias muestras de color y profundidad
+    msaa_samples: u32,
+    sample_colors: Vec<Vector3>,
+    sample_depths: Vec<f32>,
+    depth_func: DepthFunc,
+    depth_write: bool,
+    // Sin escritura de color solo se actualizan profundidad y stencil
+    color_write: bool,
+    // Fuera del viewport no se escribe (franjas del letterbox)
+    viewport: Viewport,
+    // Si hay scissor, solo se escribe dentro de ese rectángulo (en píxeles internos)
+    scissor: Option<ScreenRect>,
     background_color: Vector3,
+    // Dithering ordenado al pasar a 8 bits, contra las bandas en degradados
+    dither: bool,
+    // Texto de depuración dibujado sobre la imagen al presentar
+    overlay_text: Option<String>,
     texture: Option<Texture2D>,
 }
 
@@ -14,14 +113,35 @@ impl Framebuffer {
         Framebuffer {
             width,
             height,
+            output_width: width,
+            output_height: height,
+            ssaa_factor: 1,
             image,
+            depth_buffer: vec![f32::INFINITY; (width * height) as usize],
+            stencil_buffer: vec![0; (width * height) as usize],
+            stencil: StencilState::default(),
+            msaa_samples: 1,
+            sample_colors: Vec::new(),
+            sample_depths: Vec::new(),
+            depth_func: DepthFunc::Less,
+            depth_write: true,
+            color_write: true,
+            viewport: Viewport::new(0, 0, width, height),
+            scissor: None,
             background_color: Vector3::zero(),
+            dither: false,
+            overlay_text: None,
             texture: None,
         }
     }
 
     pub fn init_texture(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
-        self.texture = Some(rl.load_texture_from_image(thread, &self.image).unwrap());
+        let output = Image::gen_image_color(
+            self.output_width as i32,
+            self.output_height as i32,
+            Color::BLACK,
+        );
+        self.texture = Some(rl.load_texture_from_image(thread, &output).unwrap());
     }
 
     pub fn clear(&mut self) {
@@ -32,39 +152,327 @@ impl Framebuffer {
             255,
         );
         self.image.clear_background(bg_color);
+        self.depth_buffer.fill(f32::INFINITY);
+        self.stencil_buffer.fill(0);
+        self.sample_colors.fill(self.background_color);
+        self.sample_depths.fill(f32::INFINITY);
+    }
+
+    // Estado de profundidad para los draws siguientes
+    pub fn set_depth_state(&mut self, func: DepthFunc, write: bool) {
+        self.depth_func = func;
+        self.depth_write = write;
+    }
+
+    pub fn set_color_write(&mut self, write: bool) {
+        self.color_write = write;
+    }
+
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
+    }
+
+    pub fn set_scissor(&mut self, scissor: Option<ScreenRect>) {
+        self.scissor = scissor;
+    }
+
+    pub fn set_stencil_state(&mut self, stencil: StencilState) {
+        self.stencil = stencil;
+    }
+
+    // Región donde puede caer algún fragmento: la imagen recortada por el
+    // viewport y el scissor
+    pub fn raster_rect(&self) -> ScreenRect {
+        let full = ScreenRect::new(0, 0, self.width as i32 - 1, self.height as i32 - 1);
+        let visible = full.intersect(&self.viewport.rect());
+        match self.scissor {
+            Some(scissor) => visible.intersect(&scissor),
+            None => visible,
+        }
+    }
+
+    // Pruebas de límites, scissor y stencil para los fragmentos
+    fn accepts(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return false;
+        }
+        let index = (y as u32 * self.width + x as u32) as usize;
+        self.viewport.rect().contains(x, y)
+            && self.scissor.is_none_or(|rect| rect.contains(x, y))
+            && self.stencil.passes(self.stencil_buffer[index])
+    }
+
+    fn update_stencil(&mut self, x: i32, y: i32) {
+        let index = (y as u32 * self.width + x as u32) as usize;
+        self.stencil_buffer[index] = self.stencil.apply(self.stencil_buffer[index]);
+    }
+
+    // Nuevo tamaño de ventana; la textura se debe volver a crear con `init_texture`
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.output_width = width.max(1);
+        self.output_height = height.max(1);
+        self.texture = None;
+        self.set_ssaa_factor(self.ssaa_factor);
+    }
+
+    pub fn ssaa_factor(&self) -> u32 {
+        self.ssaa_factor
+    }
+
+    // Cambia la resolución interna; la textura de salida mantiene el tamaño
+    // de la ventana
+    pub fn set_ssaa_factor(&mut self, factor: u32) {
+        self.ssaa_factor = factor.max(1);
+        self.width = self.output_width * self.ssaa_factor;
+        self.height = self.output_height * self.ssaa_factor;
+        self.image = Image::gen_image_color(self.width as i32, self.height as i32, Color::BLACK);
+        self.depth_buffer = vec![f32::INFINITY; (self.width * self.height) as usize];
+        self.stencil_buffer = vec![0; (self.width * self.height) as usize];
+        self.set_msaa_samples(self.msaa_samples);
+    }
+
+    pub fn msaa_samples(&self) -> u32 {
+        self.msaa_samples
+    }
+
+    pub fn set_msaa_samples(&mut self, samples: u32) {
+        self.msaa_samples = samples.max(1);
+        let count = if self.msaa_samples > 1 {
+            (self.width * self.height * self.msaa_samples) as usize
+        } else {
+            0
+        };
+        self.sample_colors = vec![self.background_color; count];
+        self.sample_depths = vec![f32::INFINITY; count];
     }
 
     pub fn point(&mut self, x: i32, y: i32, color: Vector3) {
         if x >= 0 && y >= 0 && x < self.width as i32 && y < self.height as i32 {
-            let pixel_color = Color::new(
-                (color.x.clamp(0.0, 1.0) * 255.0) as u8,
-                (color.y.clamp(0.0, 1.0) * 255.0) as u8,
-                (color.z.clamp(0.0, 1.0) * 255.0) as u8,
-                255,
-            );
+            // Sin dithering se trunca; con dithering el umbral de redondeo
+            // cambia según la posición en la matriz de Bayer
+            let threshold = if self.dither {
+                (BAYER_4X4[(y & 3) as usize][(x & 3) as usize] as f32 + 0.5) / 16.0
+            } else {
+                0.0
+            };
+            let quantize =
+                |channel: f32| (channel.clamp(0.0, 1.0) * 255.0 + threshold).min(255.0) as u8;
+            let pixel_color =
+                Color::new(quantize(color.x), quantize(color.y), quantize(color.z), 255);
             self.image.draw_pixel(x, y, pixel_color);
         }
     }
 
+    pub fn point_with_depth(&mut self, x: i32, y: i32, depth: f32, color: Vector3) {
+        if !self.accepts(x, y) {
+            return;
+        }
+        if self.msaa_samples > 1 {
+            self.point_with_coverage(x, y, depth, color, u8::MAX);
+            return;
+        }
+
+        let index = (y as u32 * self.width + x as u32) as usize;
+        if self.depth_func.passes(depth, self.depth_buffer[index]) {
+            if self.depth_write {
+                self.depth_buffer[index] = depth;
+            }
+            self.update_stencil(x, y);
+            if self.color_write {
+                self.point(x, y, color);
+            }
+        }
+    }
+
+    // Escribe solo las muestras marcadas en `coverage` (un bit por muestra)
+    pub fn point_with_coverage(
+        &mut self,
+        x: i32,
+        y: i32,
+        depth: f32,
+        color: Vector3,
+        coverage: u8,
+    ) {
+        if !self.accepts(x, y) {
+            return;
+        }
+        if self.msaa_samples == 1 {
+            if coverage & 1 != 0 {
+                self.point_with_depth(x, y, depth, color);
+            }
+            return;
+        }
+
+        let samples = self.msaa_samples as usize;
+        let base = (y as u32 * self.width + x as u32) as usize * samples;
+        let mut written = false;
+        for sample in 0..samples {
+            if coverage & (1 << sample) == 0 {
+                continue;
+            }
+            if self.depth_func.passes(depth, self.sample_depths[base + sample]) {
+                if self.depth_write {
+                    self.sample_depths[base + sample] = depth;
+                }
+                if self.color_write {
+                    self.sample_colors[base + sample] = color;
+                }
+                written = true;
+            }
+        }
+        if written {
+            self.update_stencil(x, y);
+        }
+    }
+
+    // Mezcla source-over con lo que ya hay en el buffer
+    pub fn blend_with_coverage(
+        &mut self,
+        x: i32,
+        y: i32,
+        depth: f32,
+        color: Vector4,
+        coverage: u8,
+    ) {
+        if !self.accepts(x, y) {
+            return;
+        }
+
+        let alpha = color.w.clamp(0.0, 1.0);
+        let source = Vector3::new(color.x, color.y, color.z);
+
+        if self.msaa_samples == 1 {
+            let index = (y as u32 * self.width + x as u32) as usize;
+            if coverage & 1 == 0 || !self.depth_func.passes(depth, self.depth_buffer[index]) {
+                return;
+            }
+            if self.depth_write {
+                self.depth_buffer[index] = depth;
+            }
+            self.update_stencil(x, y);
+            if !self.color_write {
+                return;
+            }
+            let existing = self.image.get_color(x, y);
+            let destination = Vector3::new(
+                existing.r as f32 / 255.0,
+                existing.g as f32 / 255.0,
+                existing.b as f32 / 255.0,
+            );
+            self.point(x, y, source * alpha + destination * (1.0 - alpha));
+            return;
+        }
+
+        let samples = self.msaa_samples as usize;
+        let base = (y as u32 * self.width + x as u32) as usize * samples;
+        let mut written = false;
+        for sample in 0..samples {
+            if coverage & (1 << sample) == 0
+                || !self.depth_func.passes(depth, self.sample_depths[base + sample])
+            {
+                continue;
+            }
+            if self.depth_write {
+                self.sample_depths[base + sample] = depth;
+            }
+            if self.color_write {
+                let destination = self.sample_colors[base + sample];
+                self.sample_colors[base + sample] = source * alpha + destination * (1.0 - alpha);
+            }
+            written = true;
+        }
+        if written {
+            self.update_stencil(x, y);
+        }
+    }
+
+    // Promedia las muestras de cada píxel en la imagen final
+    fn resolve_samples(&mut self) {
+        let samples = self.msaa_samples as usize;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let base = (y * self.width + x) as usize * samples;
+                let mut sum = Vector3::zero();
+                for color in &self.sample_colors[base..base + samples] {
+                    sum += Vector3::new(
+                        color.x.clamp(0.0, 1.0),
+                        color.y.clamp(0.0, 1.0),
+                        color.z.clamp(0.0, 1.0),
+                    );
+                }
+                self.point(x as i32, y as i32, sum / samples as f32);
+            }
+        }
+    }
+
+    // Promedia bloques de `ssaa_factor` x `ssaa_factor` píxeles internos
+    fn downsample(&self) -> Vec<u8> {
+        let colors = self.image.get_image_data();
+        let factor = self.ssaa_factor;
+        let count = factor * factor;
+        let mut data = Vec::with_capacity((self.output_width * self.output_height * 4) as usize);
+        for y in 0..self.output_height {
+            for x in 0..self.output_width {
+                let mut sum = [0u32; 4];
+                for sy in 0..factor {
+                    let row = (y * factor + sy) * self.width;
+                    for sx in 0..factor {
+                        let c = colors[(row + x * factor + sx) as usize];
+                        sum[0] += c.r as u32;
+                        sum[1] += c.g as u32;
+                        sum[2] += c.b as u32;
+                        sum[3] += c.a as u32;
+                    }
+                }
+                data.extend(sum.iter().map(|channel| (channel / count) as u8));
+            }
+        }
+        data
+    }
+
+    pub fn set_dither(&mut self, enabled: bool) {
+        self.dither = enabled;
+    }
+
     pub fn set_background_color(&mut self, color: Vector3) {
         self.background_color = color;
     }
 
+    pub fn set_overlay_text(&mut self, text: Option<String>) {
+        self.overlay_text = text;
+    }
+
     pub fn swap_buffers(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        if self.msaa_samples > 1 {
+            self.resolve_samples();
+        }
+
+        let downsampled = if self.ssaa_factor > 1 {
+            Some(self.downsample())
+        } else {
+            None
+        };
+
         if let Some(texture) = &mut self.texture {
             let colors = self.image.get_image_data();
-            // Safely cast the &[Color] slice to a &[u8] slice for the update function
-            let data: &[u8] = unsafe {
-                std::slice::from_raw_parts(
-                    colors.as_ptr() as *const u8,
-                    colors.len() * 4, // Each Color is 4 bytes (r,g,b,a)
-                )
+            let data: &[u8] = match &downsampled {
+                Some(data) => data,
+                // Safely cast the &[Color] slice to a &[u8] slice for the update function
+                None => unsafe {
+                    std::slice::from_raw_parts(
+                        colors.as_ptr() as *const u8,
+                        colors.len() * 4, // Each Color is 4 bytes (r,g,b,a)
+                    )
+                },
             };
             texture.update_texture(data).unwrap();
 
             let mut d = rl.begin_drawing(thread);
             d.clear_background(Color::BLACK);
             d.draw_texture(texture, 0, 0, Color::WHITE);
+            if let Some(text) = &self.overlay_text {
+                d.draw_text(text, 10, 10, 20, Color::WHITE);
+            }
         } else {
             panic!("Framebuffer texture has not been initialized. Call init_texture after creating the RaylibHandle.");
         }
@@ -0,0 +1,246 @@
+use crate::belt::{AsteroidBelt, BeltSettings};
+use crate::body::{CelestialBody, Orbit, Shell};
+use crate::scene::Scene;
+use crate::shader_params::ShaderParams;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+// Más allá de esta distancia al sol el hielo no se evapora y se forman
+// gigantes gaseosos y helados
+const FROST_LINE: f32 = 420.0;
+// Franja donde puede haber agua líquida
+const HABITABLE_ZONE: std::ops::Range<f32> = 300.0..460.0;
+
+// Un sistema solar verosímil a partir de una sola semilla: la misma semilla
+// da siempre el mismo sistema
+pub fn generate_system(seed: u64) -> Scene {
+    let mut rng = StdRng::seed_from_u64(seed);
+    // Las semillas de los cuerpos siguen a la del sistema, así dos sistemas
+    // no repiten superficies
+    let mut next_seed = seed;
+    let mut take_seed = || {
+        next_seed = next_seed.wrapping_add(1);
+        next_seed
+    };
+
+    let mut star = CelestialBody::new("Sol", "star", rng.gen_range(150.0..210.0), take_seed());
+    star.rotation_period = 30.0;
+    star.light = Some(Vector3::one());
+    let mut bodies = vec![star];
+
+    // Distancias al estilo Titius–Bode: cada órbita es una fracción fija más
+    // grande que la anterior, con algo de ruido
+    let count = rng.gen_range(4..=9);
+    let ratio = rng.gen_range(1.25..1.4);
+    let mut semi_major_axis = rng.gen_range(150.0..190.0);
+    let mut orbits = Vec::new();
+    for index in 0..count {
+        orbits.push(semi_major_axis);
+        let name = format!("Planeta {}", roman(index + 1));
+        bodies.push(planet(&mut rng, &mut take_seed, name, semi_major_axis));
+        semi_major_axis *= ratio * rng.gen_range(0.92..1.08);
+    }
+
+    // A veces queda un cinturón de asteroides en el hueco más grande
+    let mut belts = Vec::new();
+    if rng.gen_bool(0.6) {
+        let (inner, outer) = orbits
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .max_by(|a, b| (a.1 - a.0).total_cmp(&(b.1 - b.0)))
+            .unwrap_or((200.0, 300.0));
+        let gap = outer - inner;
+        belts.push(AsteroidBelt::generate(&BeltSettings {
+            seed: rng.r#gen(),
+            count: rng.gen_range(200..500),
+            inner_radius: inner + gap * 0.35,
+            outer_radius: outer - gap * 0.35,
+            orbit_speed: kepler_speed(inner + gap * 0.35),
+            ..BeltSettings::default()
+        }));
+    }
+
+    // Más allá del último planeta, un cinturón helado y disperso al estilo de
+    // Kuiper, con un par de planetas enanos en órbitas inclinadas y alargadas
+    let outermost = orbits.last().copied().unwrap_or(FROST_LINE);
+    let kuiper = (outermost * 1.25, outermost * 1.7);
+    belts.push(AsteroidBelt::generate(&BeltSettings {
+        shader: "ice".to_string(),
+        seed: rng.r#gen(),
+        count: rng.gen_range(150..300),
+        inner_radius: kuiper.0,
+        outer_radius: kuiper.1,
+        inclination: 0.3,
+        eccentricity: 0.15,
+        size: 5.0,
+        orbit_speed: kepler_speed(kuiper.0),
+    }));
+    for index in 0..2 {
+        let name = format!("Enano {}", roman(index + 1));
+        let semi_major_axis = rng.gen_range(kuiper.0..kuiper.1);
+        bodies.push(dwarf_planet(
+            &mut rng,
+            &mut take_seed,
+            name,
+            semi_major_axis,
+        ));
+    }
+
+    Scene {
+        bodies,
+        belts,
+        probe: None,
+        events: Vec::new(),
+        snapshot: None,
+    }
+}
+
+fn dwarf_planet(
+    rng: &mut StdRng,
+    take_seed: &mut impl FnMut() -> u64,
+    name: String,
+    semi_major_axis: f32,
+) -> CelestialBody {
+    let shader = if rng.gen_bool(0.6) { "ice" } else { "moon" };
+    let mut body = CelestialBody::new(&name, shader, rng.gen_range(7.0..12.0), take_seed());
+    body.orbit = Some(Orbit {
+        semi_major_axis,
+        eccentricity: rng.gen_range(0.15..0.45),
+        periapsis: rng.gen_range(0.0..2.0 * PI),
+        inclination: rng.gen_range(0.2..0.8),
+        ascending_node: rng.gen_range(0.0..2.0 * PI),
+        speed: kepler_speed(semi_major_axis),
+    });
+    body.axial_tilt = rng.gen_range(0.0..2.0);
+    body.rotation_period = rng.gen_range(2.0..8.0);
+    body
+}
+
+// Un planeta suelto con sus lunas, para sumarlo a un sistema ya armado
+pub fn random_planet(seed: u64, name: String, semi_major_axis: f32) -> CelestialBody {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut next_seed = seed;
+    let mut take_seed = || {
+        next_seed = next_seed.wrapping_add(1);
+        next_seed
+    };
+    planet(&mut rng, &mut take_seed, name, semi_major_axis)
+}
+
+fn planet(
+    rng: &mut StdRng,
+    take_seed: &mut impl FnMut() -> u64,
+    name: String,
+    semi_major_axis: f32,
+) -> CelestialBody {
+    let outer = semi_major_axis > FROST_LINE;
+    let (shader, scale) = if !outer {
+        if HABITABLE_ZONE.contains(&semi_major_axis) && rng.gen_bool(0.5) {
+            ("earth", rng.gen_range(22.0..32.0))
+        } else if semi_major_axis < 200.0 && rng.gen_bool(0.3) {
+            ("lava", rng.gen_range(14.0..26.0))
+        } else {
+            ("rocky", rng.gen_range(10.0..30.0))
+        }
+    } else if rng.gen_bool(0.7) {
+        let shader = if rng.gen_bool(0.5) { "gas" } else { "giant" };
+        (shader, rng.gen_range(40.0..70.0))
+    } else {
+        ("ice", rng.gen_range(18.0..35.0))
+    };
+
+    let mut body = CelestialBody::new(&name, shader, scale, take_seed());
+    body.orbit = Some(Orbit {
+        semi_major_axis,
+        eccentricity: rng.gen_range(0.0..0.08),
+        periapsis: rng.gen_range(0.0..2.0 * PI),
+        inclination: rng.gen_range(0.0..0.06),
+        ascending_node: rng.gen_range(0.0..2.0 * PI),
+        speed: kepler_speed(semi_major_axis),
+    });
+    body.axial_tilt = rng.gen_range(0.0..0.5);
+    body.rotation_period = match shader {
+        "gas" | "giant" => rng.gen_range(1.0..2.0),
+        _ => rng.gen_range(2.0..7.0),
+    };
+
+    match shader {
+        "rocky" => {
+            body.params = ShaderParams::new()
+                .with_color("base_color", random_color(rng, 0.3..0.7))
+                .with_color("height_color", random_color(rng, 0.15..0.4))
+                .with_float("crater_scale", rng.gen_range(4.0..10.0))
+                .with_float("polar_cap", rng.gen_range(0.0..0.15));
+            if rng.gen_bool(0.3) {
+                body.shells.push(shell("thin_atmosphere", 1.08));
+            }
+        }
+        "earth" => {
+            body.params = ShaderParams::new()
+                .with_float("twilight_width", 0.25)
+                .with_color("twilight_color", Vector3::new(1.0, 0.55, 0.3));
+            body.shells.push(shell("earth_atmosphere", 1.08));
+        }
+        "gas" | "giant" => {
+            body.params = ShaderParams::new().with_float("polar_cap", rng.gen_range(0.0..0.2));
+            body.shells.push(shell("gas_atmosphere", 1.06));
+            body.rings = rng.gen_bool(0.4);
+        }
+        _ => body.rings = rng.gen_bool(0.2),
+    }
+
+    // Los gigantes atrapan más lunas; sus órbitas quedan fuera de los anillos
+    let moons = match shader {
+        "gas" | "giant" => rng.gen_range(1..=3),
+        _ => rng.gen_range(0..=2),
+    };
+    let mut moon_orbit = scale * rng.gen_range(1.5..1.8);
+    for index in 0..moons {
+        let moon_scale = rng.gen_range(3.0..(scale * 0.3).max(4.0));
+        let mut moon = CelestialBody::new(
+            &format!("{name} {}", (b'a' + index as u8) as char),
+            "moon",
+            moon_scale,
+            take_seed(),
+        );
+        moon.orbit = Some(Orbit {
+            semi_major_axis: moon_orbit,
+            eccentricity: rng.gen_range(0.0..0.1),
+            periapsis: rng.gen_range(0.0..2.0 * PI),
+            inclination: rng.gen_range(0.0..0.3),
+            ascending_node: rng.gen_range(0.0..2.0 * PI),
+            speed: rng.gen_range(2.0..5.0) / (index + 1) as f32,
+        });
+        body.children.push(moon);
+        moon_orbit += scale * rng.gen_range(0.3..0.5);
+    }
+
+    body
+}
+
+fn shell(shader: &str, scale: f32) -> Shell {
+    Shell {
+        shader: shader.to_string(),
+        scale,
+    }
+}
+
+// Tercera ley de Kepler, con velocidad 1 a 200 unidades del sol
+pub fn kepler_speed(semi_major_axis: f32) -> f32 {
+    (200.0 / semi_major_axis).powf(1.5)
+}
+
+fn random_color(rng: &mut StdRng, range: std::ops::Range<f32>) -> Vector3 {
+    Vector3::new(
+        rng.gen_range(range.clone()),
+        rng.gen_range(range.clone()),
+        rng.gen_range(range),
+    )
+}
+
+fn roman(number: usize) -> &'static str {
+    const NUMERALS: [&str; 10] = ["I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X"];
+    NUMERALS.get(number - 1).copied().unwrap_or("?")
+}
@@ -0,0 +1,411 @@
+use crate::body::{CelestialBody, SPHERE_RADIUS};
+use crate::scene_graph::build_graph;
+use raylib::prelude::*;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+// Paso fijo de integración, en unidades del ángulo de simulación
+const STEP: f32 = 0.004;
+// Tope de pasos por frame: a x1000 la simulación se atrasa en vez de congelar
+// la ventana
+const MAX_STEPS_PER_FRAME: u32 = 4000;
+// Evita aceleraciones infinitas cuando dos cuerpos casi se tocan
+const SOFTENING: f32 = 2.0;
+// μ por unidad de escala³ de los planetas y lunas
+const PLANET_DENSITY: f32 = 0.1;
+// Hasta qué fracción de la esfera de Hill de un planeta se mantienen
+// estables sus lunas
+const HILL_FRACTION: f32 = 0.5;
+// Un planeta nunca pesa más que esto respecto a lo que orbita; en escenas
+// muy comprimidas las lunas lejanas se escapan antes que desarmar el sistema
+const MAX_PLANET_FRACTION: f32 = 0.01;
+const TRAIL_LENGTH: usize = 400;
+
+// 🪐 Gravedad entre todos los cuerpos en vez de órbitas fijas. Cada cuerpo
+// atrae a todos los demás; se integra con velocity Verlet a paso fijo, que
+// conserva bien la energía en órbitas largas. Los índices son los del
+// preorden de `placed_bodies`.
+pub struct NBody {
+    pub positions: Vec<Vector3>,
+    velocities: Vec<Vector3>,
+    // Parámetro gravitacional μ = G·m de cada cuerpo
+    masses: Vec<f32>,
+    // Radio de la esfera de cada cuerpo, para los choques
+    radii: Vec<f32>,
+    accelerations: Vec<Vector3>,
+    // Posiciones recientes de cada cuerpo, para ver cómo evoluciona
+    pub trails: Vec<VecDeque<Vector3>>,
+    // Tiempo que quedó sin integrar el frame anterior
+    pending: f32,
+}
+
+impl NBody {
+    // Arranca donde están los cuerpos en sus órbitas en `angle`, así activar
+    // la gravedad no da un salto
+    pub fn from_orbits(bodies: &[CelestialBody], center: Vector3, angle: f32) -> Self {
+        let positions_at = |angle: f32| -> Vec<Vector3> {
+            build_graph(bodies, center, angle, None)
+                .placed_bodies()
+                .iter()
+                .map(|placed| placed.position)
+                .collect()
+        };
+        let h = 1e-3;
+        let positions = positions_at(angle);
+        let scene_velocities: Vec<Vector3> = positions_at(angle - h)
+            .iter()
+            .zip(positions_at(angle + h))
+            .map(|(&before, after)| (after - before) / (2.0 * h))
+            .collect();
+
+        let mut flat = Vec::new();
+        flatten(bodies, None, &mut flat);
+        let (masses, central_mass) = assign_masses(&flat);
+
+        // La dirección del movimiento sale de la órbita de la escena y la
+        // rapidez de vis-viva, v² = μ(2/r - 1/a), con la masa que lo atrae:
+        // así la órbita arranca ligada aunque la escena no siga a Kepler
+        let mut velocities: Vec<Vector3> = Vec::with_capacity(flat.len());
+        for (index, entry) in flat.iter().enumerate() {
+            let (origin, origin_velocity, mu) = match entry.parent {
+                Some(parent) => (positions[parent], velocities[parent], masses[parent]),
+                None => (center, Vector3::zero(), central_mass),
+            };
+            let velocity = match entry.body.orbit {
+                Some(orbit) if !is_central(entry) => {
+                    let relative = scene_velocities[index]
+                        - entry
+                            .parent
+                            .map_or(Vector3::zero(), |parent| scene_velocities[parent]);
+                    let r = (positions[index] - origin).length().max(1.0);
+                    let speed = (mu * (2.0 / r - 1.0 / orbit.semi_major_axis))
+                        .max(0.0)
+                        .sqrt();
+                    origin_velocity + relative.normalized() * speed
+                }
+                Some(_) => scene_velocities[index],
+                None => origin_velocity,
+            };
+            velocities.push(velocity);
+        }
+
+        // Sin momento neto, para que el sistema no se vaya de la cámara
+        let total_mass: f32 = masses.iter().sum();
+        let drift = velocities
+            .iter()
+            .zip(&masses)
+            .fold(Vector3::zero(), |sum, (&velocity, &mass)| {
+                sum + velocity * mass
+            })
+            / total_mass;
+        for velocity in &mut velocities {
+            *velocity -= drift;
+        }
+
+        NBody::new(positions, velocities, masses, radii(&flat))
+    }
+
+    // Sigue una simulación guardada; None si no es de estos mismos cuerpos
+    pub fn resume(
+        bodies: &[CelestialBody],
+        positions: Vec<Vector3>,
+        velocities: Vec<Vector3>,
+    ) -> Option<Self> {
+        let mut flat = Vec::new();
+        flatten(bodies, None, &mut flat);
+        let (masses, _) = assign_masses(&flat);
+        (positions.len() == masses.len() && velocities.len() == masses.len())
+            .then(|| NBody::new(positions, velocities, masses, radii(&flat)))
+    }
+
+    fn new(
+        positions: Vec<Vector3>,
+        velocities: Vec<Vector3>,
+        masses: Vec<f32>,
+        radii: Vec<f32>,
+    ) -> Self {
+        NBody {
+            accelerations: accelerations(&positions, &masses),
+            trails: vec![VecDeque::new(); positions.len()],
+            positions,
+            velocities,
+            masses,
+            radii,
+            pending: 0.0,
+        }
+    }
+
+    pub fn velocities(&self) -> &[Vector3] {
+        &self.velocities
+    }
+
+    // Avanza `elapsed` y devuelve los choques que hubo, en orden: cada uno
+    // ya sacó de la simulación al cuerpo absorbido
+    pub fn step(&mut self, elapsed: f32) -> Vec<Merge> {
+        let mut merges = Vec::new();
+        self.pending = (self.pending + elapsed).min(STEP * MAX_STEPS_PER_FRAME as f32);
+        while self.pending >= STEP {
+            self.pending -= STEP;
+            self.verlet(STEP);
+            while let Some(merge) = self.merge_collision() {
+                merges.push(merge);
+            }
+        }
+        for (trail, &position) in self.trails.iter_mut().zip(&self.positions) {
+            if trail.back() != Some(&position) {
+                trail.push_back(position);
+                if trail.len() > TRAIL_LENGTH {
+                    trail.pop_front();
+                }
+            }
+        }
+        merges
+    }
+
+    // Dos esferas que se tocan se funden en la más pesada: suma de masas y
+    // de volúmenes, y se conserva el momento
+    fn merge_collision(&mut self) -> Option<Merge> {
+        let count = self.positions.len();
+        let (a, b) = (0..count)
+            .flat_map(|a| (a + 1..count).map(move |b| (a, b)))
+            .find(|&(a, b)| {
+                self.positions[a].distance_to(self.positions[b]) < self.radii[a] + self.radii[b]
+            })?;
+        let (survivor, absorbed) = if self.masses[a] >= self.masses[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+
+        let (m1, m2) = (self.masses[survivor], self.masses[absorbed]);
+        let mass = m1 + m2;
+        let position = (self.positions[survivor] * m1 + self.positions[absorbed] * m2) / mass;
+        let velocity = (self.velocities[survivor] * m1 + self.velocities[absorbed] * m2) / mass;
+        let radius = (self.radii[survivor].powi(3) + self.radii[absorbed].powi(3)).cbrt();
+        self.positions[survivor] = position;
+        self.velocities[survivor] = velocity;
+        self.masses[survivor] = mass;
+        self.radii[survivor] = radius;
+        self.remove(absorbed..absorbed + 1);
+
+        Some(Merge {
+            survivor,
+            absorbed,
+            position,
+            velocity,
+            radius,
+        })
+    }
+
+    // Posición con la aceleración actual; velocidad con el promedio entre la
+    // aceleración actual y la de la nueva posición
+    fn verlet(&mut self, h: f32) {
+        for ((position, velocity), acceleration) in self
+            .positions
+            .iter_mut()
+            .zip(&self.velocities)
+            .zip(&self.accelerations)
+        {
+            *position += *velocity * h + *acceleration * (0.5 * h * h);
+        }
+        let next = accelerations(&self.positions, &self.masses);
+        for ((velocity, acceleration), next) in self
+            .velocities
+            .iter_mut()
+            .zip(&self.accelerations)
+            .zip(&next)
+        {
+            *velocity += (*acceleration + *next) * (0.5 * h);
+        }
+        self.accelerations = next;
+    }
+
+    // Multiplica la rapidez del cuerpo respecto al que más lo atrae: > 1
+    // agranda su órbita, < 1 la achica
+    pub fn kick(&mut self, index: usize, factor: f32) {
+        let Some(&position) = self.positions.get(index) else {
+            return;
+        };
+        let reference = self
+            .strongest_pull(position, Some(index))
+            .map_or(Vector3::zero(), |other| self.velocities[other]);
+        self.velocities[index] = reference + (self.velocities[index] - reference) * factor;
+    }
+
+    // Suma un cuerpo al final del preorden, en `position` y en órbita
+    // circular alrededor de lo que más lo atrae; sus lunas van alrededor de
+    // él, donde las pone su órbita en `angle`
+    pub fn add(&mut self, body: &CelestialBody, position: Vector3, angle: f32) {
+        let (center, center_velocity, mu) =
+            self.strongest_pull(position, None)
+                .map_or((position, Vector3::zero(), 0.0), |other| {
+                    (
+                        self.positions[other],
+                        self.velocities[other],
+                        self.masses[other],
+                    )
+                });
+        self.push(body, position, center, center_velocity, mu, angle);
+        self.accelerations = accelerations(&self.positions, &self.masses);
+    }
+
+    fn push(
+        &mut self,
+        body: &CelestialBody,
+        position: Vector3,
+        center: Vector3,
+        center_velocity: Vector3,
+        mu: f32,
+        angle: f32,
+    ) {
+        // En el plano XY y en el sentido de las órbitas de la escena
+        let radial = position - center;
+        let tangent = Vector3::new(0.0, 0.0, 1.0).cross(radial).normalized();
+        let speed = (mu / radial.length().max(1.0)).sqrt();
+        let velocity = center_velocity + tangent * speed;
+        let mass = body.mass.unwrap_or_else(|| planet_mass(body, mu));
+        self.positions.push(position);
+        self.velocities.push(velocity);
+        self.masses.push(mass);
+        self.radii.push(body.scale * SPHERE_RADIUS);
+        self.trails.push(VecDeque::new());
+        for moon in &body.children {
+            let offset = moon
+                .orbit
+                .map_or(Vector3::zero(), |orbit| orbit.offset(angle));
+            self.push(moon, position + offset, position, velocity, mass, angle);
+        }
+    }
+
+    // Saca los cuerpos de `range` (un cuerpo y sus lunas son contiguos)
+    pub fn remove(&mut self, range: Range<usize>) {
+        self.positions.drain(range.clone());
+        self.velocities.drain(range.clone());
+        self.masses.drain(range.clone());
+        self.radii.drain(range.clone());
+        self.trails.drain(range);
+        self.accelerations = accelerations(&self.positions, &self.masses);
+    }
+
+    // El cuerpo que más atrae a `position`, sin contar a `except`
+    fn strongest_pull(&self, position: Vector3, except: Option<usize>) -> Option<usize> {
+        let pull = |other: usize| {
+            let delta = self.positions[other] - position;
+            self.masses[other] / delta.dot(delta).max(1.0)
+        };
+        (0..self.positions.len())
+            .filter(|&other| Some(other) != except)
+            .max_by(|&a, &b| pull(a).total_cmp(&pull(b)))
+    }
+}
+
+fn accelerations(positions: &[Vector3], masses: &[f32]) -> Vec<Vector3> {
+    let mut result = vec![Vector3::zero(); positions.len()];
+    for i in 0..positions.len() {
+        for j in i + 1..positions.len() {
+            let delta = positions[j] - positions[i];
+            let distance_sqr = delta.dot(delta) + SOFTENING * SOFTENING;
+            let inverse_cube = 1.0 / (distance_sqr * distance_sqr.sqrt());
+            result[i] += delta * (masses[j] * inverse_cube);
+            result[j] -= delta * (masses[i] * inverse_cube);
+        }
+    }
+    result
+}
+
+// Un choque: `absorbed` se fundió en `survivor` (índices de antes de sacar a
+// `absorbed`), que quedó en `position` con el radio de los dos juntos
+pub struct Merge {
+    pub survivor: usize,
+    pub absorbed: usize,
+    pub position: Vector3,
+    pub velocity: Vector3,
+    pub radius: f32,
+}
+
+fn radii(flat: &[Entry]) -> Vec<f32> {
+    flat.iter()
+        .map(|entry| entry.body.scale * SPHERE_RADIUS)
+        .collect()
+}
+
+// Un cuerpo del preorden con el índice de su padre
+struct Entry<'a> {
+    body: &'a CelestialBody,
+    parent: Option<usize>,
+}
+
+fn flatten<'a>(bodies: &'a [CelestialBody], parent: Option<usize>, flat: &mut Vec<Entry<'a>>) {
+    for body in bodies {
+        let index = flat.len();
+        flat.push(Entry { body, parent });
+        flatten(&body.children, Some(index), flat);
+    }
+}
+
+// Las estrellas y lo que queda quieto en el centro del sistema
+fn is_central(entry: &Entry) -> bool {
+    entry.parent.is_none() && (entry.body.light.is_some() || entry.body.orbit.is_none())
+}
+
+// μ de cada cuerpo en preorden y la suma de las estrellas del centro
+fn assign_masses(flat: &[Entry]) -> (Vec<f32>, f32) {
+    // Las estrellas sin masa explícita se reparten lo que piden las órbitas
+    // de los planetas
+    let stars = flat.iter().filter(|entry| is_central(entry)).count();
+    let planets = flat
+        .iter()
+        .filter(|entry| entry.parent.is_none() && !is_central(entry));
+    let shared = kepler_mass(planets.map(|entry| entry.body)).unwrap_or(1.0) / stars.max(1) as f32;
+    let central_mass: f32 = flat
+        .iter()
+        .filter(|entry| is_central(entry))
+        .map(|entry| entry.body.mass.unwrap_or(shared))
+        .sum();
+
+    // En preorden el padre se resuelve antes que sus lunas
+    let mut masses: Vec<f32> = Vec::with_capacity(flat.len());
+    for entry in flat {
+        let mass = match entry.body.mass {
+            Some(mass) => mass,
+            None if is_central(entry) => shared,
+            None => {
+                let attractor = entry.parent.map_or(central_mass, |parent| masses[parent]);
+                planet_mass(entry.body, attractor)
+            }
+        };
+        masses.push(mass);
+    }
+    (masses, central_mass)
+}
+
+// Según su tamaño, pero con lo necesario para retener a sus lunas: cada una
+// debe quedar dentro de una fracción de su esfera de Hill, a·(m / 3M)^(1/3)
+fn planet_mass(body: &CelestialBody, attractor: f32) -> f32 {
+    let own = PLANET_DENSITY * body.scale.powi(3);
+    let Some(orbit) = body.orbit else {
+        return own;
+    };
+    let closest = (orbit.semi_major_axis * (1.0 - orbit.eccentricity)).max(1.0);
+    body.children
+        .iter()
+        .filter_map(|moon| moon.orbit)
+        .map(|moon| {
+            let farthest = moon.semi_major_axis * (1.0 + moon.eccentricity);
+            3.0 * attractor * (farthest / (HILL_FRACTION * closest)).powi(3)
+        })
+        .fold(own, f32::max)
+        .min(own.max(attractor * MAX_PLANET_FRACTION))
+}
+
+// Tercera ley de Kepler, n²·a³ = μ, con la mediana de las órbitas
+// por si las velocidades de la escena no son del todo coherentes
+fn kepler_mass<'a>(bodies: impl Iterator<Item = &'a CelestialBody>) -> Option<f32> {
+    let mut estimates: Vec<f32> = bodies
+        .filter_map(|body| body.orbit)
+        .filter(|orbit| orbit.semi_major_axis > 0.0)
+        .map(|orbit| orbit.speed.powi(2) * orbit.semi_major_axis.powi(3))
+        .collect();
+    estimates.sort_by(f32::total_cmp);
+    estimates.get(estimates.len() / 2).copied()
+}
@@ -0,0 +1,96 @@
+use crate::Uniforms;
+use crate::fragment::Fragment;
+use crate::shaders::FragmentShader;
+use raylib::prelude::*;
+
+// Cómo se combina una capa con lo que ya acumularon las de abajo
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LayerBlend {
+    // Encima según el alfa de la capa
+    Normal,
+    // Suma luz, como un brillo
+    Add,
+    // Oscurece o tiñe lo de abajo
+    Multiply,
+}
+
+pub struct Layer<'a> {
+    pub shader: &'a dyn FragmentShader,
+    pub blend: LayerBlend,
+    // Multiplica el alfa de la capa
+    pub opacity: f32,
+}
+
+// Un cuerpo armado con varias capas (superficie, nubes, ...) que se evalúan
+// en orden para cada fragmento, en vez de un shader monolítico por planeta
+pub struct LayeredShader<'a> {
+    layers: Vec<Layer<'a>>,
+}
+
+impl<'a> LayeredShader<'a> {
+    // La primera capa es la base y define si el cuerpo es emisivo
+    pub fn new(base: &'a dyn FragmentShader) -> Self {
+        LayeredShader {
+            layers: vec![Layer {
+                shader: base,
+                blend: LayerBlend::Normal,
+                opacity: 1.0,
+            }],
+        }
+    }
+
+    pub fn with_layer(
+        mut self,
+        shader: &'a dyn FragmentShader,
+        blend: LayerBlend,
+        opacity: f32,
+    ) -> Self {
+        self.layers.push(Layer {
+            shader,
+            blend,
+            opacity,
+        });
+        self
+    }
+}
+
+impl FragmentShader for LayeredShader<'_> {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector4 {
+        let mut color = Vector3::zero();
+        let mut alpha = 0.0;
+        for layer in &self.layers {
+            let c = layer.shader.shade(fragment, uniforms);
+            let amount = (c.w * layer.opacity).clamp(0.0, 1.0);
+            let rgb = Vector3::new(c.x, c.y, c.z);
+            match layer.blend {
+                LayerBlend::Normal => {
+                    color = color.lerp(rgb, amount);
+                    alpha += amount * (1.0 - alpha);
+                }
+                LayerBlend::Add => color += rgb * amount,
+                LayerBlend::Multiply => {
+                    color *= Vector3::one().lerp(rgb, amount);
+                }
+            }
+        }
+        Vector4::new(color.x, color.y, color.z, alpha)
+    }
+
+    fn emissive(&self) -> bool {
+        self.layers[0].shader.emissive()
+    }
+
+    // El relieve es el de la base
+    fn normal(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
+        self.layers[0].shader.normal(fragment, uniforms)
+    }
+
+    // Las emisiones se suman sin que las capas de arriba las tapen
+    fn emission(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
+        self.layers
+            .iter()
+            .map(|layer| layer.shader.emission(fragment, uniforms) * layer.opacity)
+            .fold(Vector3::zero(), |sum, emission| sum + emission)
+    }
+}
@@ -0,0 +1,178 @@
+use crate::shader_params::ShaderParams;
+use raylib::prelude::*;
+
+// Luz mínima para que el lado nocturno no quede completamente negro
+const AMBIENT: f32 = 0.06;
+// Un fragmento a menos de esto (en radios) del centro de una esfera es de
+// ese mismo cuerpo, sus nubes o su atmósfera: no se hace sombra a sí mismo
+const SELF_SHADOW_MARGIN: f32 = 1.25;
+
+// Luz puntual de una estrella; un sistema binario tiene dos
+#[derive(Clone, Copy, Debug)]
+pub struct Light {
+    pub position: Vector3,
+    pub color: Vector3,
+    // Radio de la estrella: da el ancho de la penumbra en los eclipses
+    pub radius: f32,
+}
+
+// Esfera que puede tapar a una estrella: las lunas oscurecen a su planeta y
+// los planetas a sus lunas y anillos
+#[derive(Clone, Copy, Debug)]
+pub struct Occluder {
+    pub center: Vector3,
+    pub radius: f32,
+}
+
+// La estrella más cercana a `position`: la que decide el día y la noche de
+// los shaders. Sin luces, el origen.
+pub fn nearest_light(lights: &[Light], position: Vector3) -> Vector3 {
+    lights
+        .iter()
+        .map(|light| light.position)
+        .min_by(|a, b| a.distance_to(position).total_cmp(&b.distance_to(position)))
+        .unwrap_or(Vector3::zero())
+}
+
+// Franja crepuscular de los cuerpos con atmósfera: en vez de cortar en
+// N·L = 0, la luz se apaga a lo largo de `width` y se tiñe de `color`
+#[derive(Clone, Copy, Debug)]
+pub struct Twilight {
+    pub width: f32,
+    pub color: Vector3,
+}
+
+impl Twilight {
+    // Se activa desde los parámetros del draw con `twilight_width` > 0
+    pub fn from_params(params: &ShaderParams) -> Option<Twilight> {
+        let width = params.float("twilight_width", 0.0);
+        (width > 0.0).then(|| Twilight {
+            width,
+            color: params.color("twilight_color", Vector3::new(1.0, 0.5, 0.25)),
+        })
+    }
+}
+
+// Color de la luz que llega al fragmento: el ambiente más el aporte de cada
+// estrella teñido de su color, con la franja crepuscular si hay y menos lo
+// que tapen los eclipses
+pub fn diffuse(
+    normal: Vector3,
+    world_position: Vector3,
+    lights: &[Light],
+    occluders: &[Occluder],
+    twilight: Option<Twilight>,
+) -> Vector3 {
+    lights
+        .iter()
+        .fold(Vector3::new(AMBIENT, AMBIENT, AMBIENT), |total, light| {
+            let n_dot_l = normal.dot((light.position - world_position).normalized());
+            let light_in = incoming(n_dot_l, twilight);
+            if light_in.x + light_in.y + light_in.z <= 0.0 {
+                return total;
+            }
+            total + light.color * light_in * visibility(light, world_position, occluders)
+        })
+}
+
+// Brillo especular Blinn-Phong: la luz de cada estrella reflejada hacia la
+// cámara, más concentrada cuanto mayor es `shininess`. Se apaga al cruzar al
+// lado nocturno y en los eclipses, como el difuso.
+pub fn specular(
+    normal: Vector3,
+    world_position: Vector3,
+    camera_position: Vector3,
+    lights: &[Light],
+    occluders: &[Occluder],
+    shininess: f32,
+) -> Vector3 {
+    let to_camera = (camera_position - world_position).normalized();
+    lights.iter().fold(Vector3::zero(), |total, light| {
+        let to_light = (light.position - world_position).normalized();
+        let n_dot_l = normal.dot(to_light);
+        if n_dot_l <= 0.0 {
+            return total;
+        }
+        let half = (to_light + to_camera).normalized();
+        let highlight = normal.dot(half).max(0.0).powf(shininess) * (n_dot_l / 0.1).min(1.0);
+        total + light.color * highlight * visibility(light, world_position, occluders)
+    })
+}
+
+// Fracción del disco de la estrella que se ve desde `position`, comparando
+// los tamaños angulares de la estrella y de cada esfera en el camino. Umbra
+// donde la esfera la cubre entera y penumbra suave en el borde del cono.
+fn visibility(light: &Light, position: Vector3, occluders: &[Occluder]) -> f32 {
+    let to_light = light.position - position;
+    let light_distance = to_light.length();
+    let direction = to_light / light_distance;
+    let light_size = light.radius / light_distance;
+    let light_angle = light_size.min(1.0).asin();
+    occluders.iter().fold(1.0, |visible, occluder| {
+        let to_occluder = occluder.center - position;
+        let along = to_occluder.dot(direction);
+        if along <= 0.0 || along >= light_distance {
+            return visible;
+        }
+        // Descarte barato: lejos del cono de sombra, con la penumbra y algo de
+        // margen para no cortarla
+        let distance_sqr = to_occluder.dot(to_occluder);
+        let reach = 1.25 * (occluder.radius + along * light_size);
+        if distance_sqr - along * along > reach * reach {
+            return visible;
+        }
+        let distance = distance_sqr.sqrt();
+        if distance < occluder.radius * SELF_SHADOW_MARGIN {
+            return visible;
+        }
+
+        let occluder_angle = (occluder.radius / distance).asin();
+        let separation = (along / distance).clamp(-1.0, 1.0).acos();
+        let outer = light_angle + occluder_angle;
+        if separation >= outer {
+            return visible;
+        }
+        // Lo más que puede tapar: toda la estrella, o un anillo de ella si la
+        // esfera se ve más chica
+        let full = if occluder_angle >= light_angle {
+            1.0
+        } else {
+            (occluder_angle / light_angle).powi(2)
+        };
+        let inner = (occluder_angle - light_angle).abs();
+        let t = ((outer - separation) / (outer - inner).max(1e-6)).clamp(0.0, 1.0);
+        visible * (1.0 - full * t * t * (3.0 - 2.0 * t))
+    })
+}
+
+// Difuso de Lambert de una luz blanca sin el ambiente
+fn incoming(n_dot_l: f32, twilight: Option<Twilight>) -> Vector3 {
+    let Some(twilight) = twilight else {
+        let d = (1.0 - AMBIENT) * n_dot_l.max(0.0);
+        return Vector3::new(d, d, d);
+    };
+
+    let w = twilight.width;
+    // Pasa suave de 0 a N·L entre -w y w
+    let t = ((n_dot_l + w) / (2.0 * w)).clamp(0.0, 1.0);
+    let smooth = t * t * (3.0 - 2.0 * t);
+    let intensity = smooth * (n_dot_l + w) / (1.0 + w);
+    // Cerca del terminador la luz es más cálida
+    let band = (1.0 - n_dot_l.abs() / w).max(0.0);
+    let color = Vector3::new(1.0, 1.0, 1.0).lerp(twilight.color, band);
+    color * ((1.0 - AMBIENT) * intensity)
+}
+
+// Estilo caricatura: la intensidad de la luz se redondea a pocos niveles
+const TOON_BANDS: f32 = 4.0;
+// Con N·V por debajo de esto el fragmento es parte del contorno
+pub const TOON_OUTLINE: f32 = 0.25;
+
+pub fn toon(diffuse: Vector3) -> Vector3 {
+    let intensity = diffuse.x.max(diffuse.y).max(diffuse.z);
+    if intensity <= 0.0 {
+        return diffuse;
+    }
+    let banded = ((intensity * TOON_BANDS).ceil() / TOON_BANDS).max(AMBIENT);
+    diffuse * (banded / intensity)
+}
@@ -1,12 +1,12 @@
 use crate::fragment::Fragment;
-use crate::vertex::Vertex;
+use crate::vertex_output::VertexOutput;
 use raylib::math::Vector3;
 
-pub fn line(a: &Vertex, b: &Vertex) -> Vec<Fragment> {
+pub fn line(a: &VertexOutput, b: &VertexOutput) -> Vec<Fragment> {
     let mut fragments = Vec::new();
 
-    let start = a.transformed_position;
-    let end = b.transformed_position;
+    let start = a.screen_position;
+    let end = b.screen_position;
 
     let mut x0 = start.x as i32;
     let mut y0 = start.y as i32;
@@ -21,8 +21,12 @@ pub fn line(a: &Vertex, b: &Vertex) -> Vec<Fragment> {
 
     let mut err = if dx > dy { dx / 2 } else { -dy / 2 };
 
+    // La profundidad se interpola según el avance sobre el eje dominante
+    let steps = dx.max(dy).max(1) as f32;
+    let mut step = 0;
+
     loop {
-        let z = start.z + (end.z - start.z) * (x0 - start.x as i32) as f32 / (end.x - start.x) as f32;
+        let z = start.z + (end.z - start.z) * step as f32 / steps;
         // For now, we'll just use white for the line color.
         // A more advanced implementation would interpolate the vertex colors.
         fragments.push(Fragment::new(x0 as f32, y0 as f32, Vector3::new(1.0, 1.0, 1.0), z));
@@ -38,6 +42,7 @@ pub fn line(a: &Vertex, b: &Vertex) -> Vec<Fragment> {
             err += dx;
             y0 += sy;
         }
+        step += 1;
     }
 
     fragments
@@ -1,25 +1,163 @@
+mod belt;
+mod body;
+mod bookmarks;
+mod bump;
+mod camera;
+mod clip;
+mod clock;
+mod color_ramp;
+mod events;
 mod fragment;
 mod framebuffer;
+mod generator;
+mod gravity;
+mod layers;
+mod lighting;
 mod line;
 mod matrix;
+mod noise;
 mod obj;
+mod particles;
+mod probe;
+mod render_state;
+mod renderer;
+mod scene;
+mod scene_graph;
+mod script;
+mod shader_params;
+mod shader_registry;
 mod shaders;
+mod stats;
+mod text;
+mod texture;
+mod tiles;
 mod triangle;
+mod trojans;
 mod vertex;
+mod vertex_output;
+mod viewport;
 
+use crate::clip::clip_triangle_against_frustum;
 use crate::matrix::new_matrix4;
-use framebuffer::Framebuffer;
+use belt::AsteroidBelt;
+use body::{CelestialBody, SPHERE_RADIUS, body_mut, count_bodies, dissolve_body, remove_body};
+use bookmarks::Bookmarks;
+use camera::{Camera, CameraMode, Chase, SmoothCamera, Transition};
+use clock::{RADIANS_PER_SECOND, SimulationClock};
+use events::{EventRegistry, World};
+use fragment::Fragment;
+use framebuffer::{DepthFunc, Framebuffer, StencilFunc, StencilOp, StencilState};
+use gravity::NBody;
+use layers::{LayerBlend, LayeredShader};
+use lighting::{Light, Occluder, Twilight};
+use line::line;
 use obj::Obj;
+use particles::{CometTail, ParticleSystem};
+use probe::{Probe, ProbeSettings};
 use raylib::prelude::*;
-use shaders::{gas_shader, rocky_shader, star_shader, vertex_shader};
+use render_state::{BlendMode, CullMode, RenderMode, RenderState};
+use renderer::{CommandBuffer, DrawCommand, Instance, MeshHandle, Renderer};
+use scene::Snapshot;
+use scene_graph::{NodeContent, build_graph};
+use shader_params::{NO_PARAMS, ShaderParams};
+use shader_registry::ShaderRegistry;
+use shaders::{
+    AtmosphereShader, Aurora, CloudShader, EarthShader, FragmentShader, GalaxyShader, GasShader,
+    GreatStorm, IceShader, LavaShader, MoonShader, NebulaShader, RingShader, RockyShader,
+    ScriptShader, SkyboxShader, SolidShader, StarShader, TailShader, vertex_shader,
+    viewport_transform,
+};
+use std::cmp::Ordering;
 use std::f32::consts::PI;
 use std::thread;
 use std::time::Duration;
-use triangle::triangle;
+use texture::{Cubemap, Texture};
+use tiles::TileBinner;
+use triangle::triangle_in_rect;
+use trojans::{Trojans, lagrange_points};
 use vertex::Vertex;
+use vertex_output::VertexOutput;
+use viewport::Viewport;
 
-pub struct Uniforms {
+// Tamaño en píxeles de un punto a distancia 1; se divide entre w
+const POINT_SIZE_SCALE: f32 = 1500.0;
+
+// Aristas del modo relleno + alambre: color de contraste y un pequeño
+// adelanto en profundidad para que no peleen con el relleno
+const OVERLAY_WIRE_COLOR: Vector3 = Vector3::new(0.05, 0.05, 0.05);
+const OVERLAY_WIRE_DEPTH_BIAS: f32 = -1e-5;
+const OVERLAY_WIRE_SLOPE_BIAS: f32 = -1.0;
+
+// Anillos y líneas de órbita van en el mismo plano que la superficie que
+// cruzan; este adelanto evita que parpadeen contra ella
+const COPLANAR_DEPTH_BIAS: f32 = -1e-7;
+const COPLANAR_SLOPE_BIAS: f32 = -1.0;
+
+// Contorno del cuerpo seleccionado: el cuerpo marca el stencil y una esfera
+// un poco más grande (también que sus atmósferas) se pinta solo fuera de esa
+// marca
+const OUTLINE_SCALE: f32 = 1.12;
+const OUTLINE_COLOR: Vector3 = Vector3::new(0.9, 0.8, 0.3);
+const SELECTED_STENCIL: u8 = 1;
+
+// Los nombres se ven enteros hasta esta distancia a la cámara y desaparecen
+// del todo en la segunda
+const LABEL_FADE_START: f32 = 1200.0;
+const LABEL_FADE_END: f32 = 3000.0;
+
+// Escombros de cada choque en el modo de gravedad
+const DEBRIS_PARTICLES: f32 = 250.0;
+const DEBRIS_LIFETIME: f32 = 3.0;
+
+// Sin argumentos, o si la partida de `--resume` no se puede cargar
+const DEFAULT_SCENE: &str = "assets/scenes/sistema.toml";
+// F5 guarda la partida aquí y F9 (o `--resume`) la retoma
+const SAVE_PATH: &str = "partida.toml";
+// Cámaras de Ctrl+1..9, que se conservan entre ejecuciones
+const BOOKMARKS_PATH: &str = "camaras.toml";
+const BOOKMARK_TRANSITION: f32 = 1.2;
+// Flechas en radianes por segundo; A/S acercan o alejan un factor e^ZOOM_SPEED
+// por segundo
+const ORBIT_SPEED: f32 = 2.1;
+const ZOOM_SPEED: f32 = 1.2;
+
+// Radio en píxeles por debajo del cual un cuerpo se dibuja con pocos polígonos
+const LOW_DETAIL_PIXELS: f32 = 6.0;
+
+// Vuelo libre: unidades por segundo, multiplicador con Shift y radianes por
+// píxel que se mueve el mouse
+const FLY_SPEED: f32 = 150.0;
+const FLY_BOOST: f32 = 5.0;
+const MOUSE_SENSITIVITY: f32 = 0.003;
+
+// Tamaño de la sonda y dónde va la cámara a bordo: detrás y por encima del
+// plano de las órbitas
+const PROBE_SIZE: f32 = 3.0;
+const RIDE_BEHIND: f32 = 40.0;
+const RIDE_ABOVE: f32 = 12.0;
+
+pub struct Uniforms<'a> {
     pub model_matrix: Matrix,
+    pub view_matrix: Matrix,
+    pub projection_matrix: Matrix,
+    pub viewport_matrix: Matrix,
+    pub camera_position: Vector3,
+    // Estrellas del sistema: una, o dos en un binario
+    pub lights: &'a [Light],
+    // Planetas y lunas que pueden eclipsar a las estrellas
+    pub occluders: &'a [Occluder],
+    pub params: &'a ShaderParams,
+    // Semilla del cuerpo: desplaza el ruido para que cada uno sea distinto
+    pub seed: u64,
+    // Segundos desde el inicio, para animar los shaders
+    pub time: f32,
+}
+
+impl Uniforms<'_> {
+    // La estrella que decide el día y la noche en `position`
+    pub fn nearest_light(&self, position: Vector3) -> Vector3 {
+        lighting::nearest_light(self.lights, position)
+    }
 }
 
 fn create_model_matrix(translation: Vector3, scale: f32, rotation: Vector3) -> Matrix {
@@ -66,36 +204,505 @@ fn create_model_matrix(translation: Vector3, scale: f32, rotation: Vector3) -> M
     scale_matrix * rotation_matrix * translation_matrix
 }
 
+fn create_viewport_matrix(x: f32, y: f32, width: f32, height: f32) -> Matrix {
+    // Y se invierte porque en pantalla crece hacia abajo; Z pasa de [-1, 1] a [0, 1]
+    new_matrix4(
+        width / 2.0,
+        0.0,
+        0.0,
+        x + width / 2.0,
+        0.0,
+        -height / 2.0,
+        0.0,
+        y + height / 2.0,
+        0.0,
+        0.0,
+        0.5,
+        0.5,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    )
+}
+
+// Los modelos usan orden antihorario; como el viewport invierte Y, en pantalla
+// las caras frontales quedan con área con signo negativa
+fn is_backface(v0: &VertexOutput, v1: &VertexOutput, v2: &VertexOutput) -> bool {
+    let a = v0.screen_position;
+    let b = v1.screen_position;
+    let c = v2.screen_position;
+    let signed_area = (b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y);
+    signed_area >= 0.0
+}
+
+// Una proyección en perspectiva copia -z a w (fila 3 = [0, 0, -1, 0]);
+// una ortográfica deja w = 1
+fn is_perspective(projection_matrix: &Matrix) -> bool {
+    projection_matrix.m11 != 0.0
+}
+
+// Desplaza la profundidad del triángulo en pantalla según el depth bias del draw
+fn apply_depth_bias(screen_tri: &mut [VertexOutput; 3], state: &RenderState) {
+    let a = screen_tri[0].screen_position;
+    let b = screen_tri[1].screen_position;
+    let c = screen_tri[2].screen_position;
+
+    let mut slope = 0.0;
+    let area = (b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y);
+    if area != 0.0 {
+        let dz_dx = ((b.z - a.z) * (c.y - a.y) - (c.z - a.z) * (b.y - a.y)) / area;
+        let dz_dy = ((c.z - a.z) * (b.x - a.x) - (b.z - a.z) * (c.x - a.x)) / area;
+        slope = dz_dx.abs().max(dz_dy.abs());
+    }
+
+    let bias = state.depth_bias + state.slope_depth_bias * slope;
+    for vertex in screen_tri.iter_mut() {
+        vertex.screen_position.z += bias;
+    }
+}
+
+fn rgb(color: Vector4) -> Vector3 {
+    Vector3::new(color.x, color.y, color.z)
+}
+
+// Corre el fragment shader y, si el draw está iluminado y el shader no es
+// emisivo, le aplica el difuso (en bandas con `toon`); la emisión se suma al
+// final sin iluminar
+fn shade(
+    shader: &dyn FragmentShader,
+    fragment: &Fragment,
+    uniforms: &Uniforms,
+    state: &RenderState,
+) -> Vector4 {
+    let color = shader.shade(fragment, uniforms);
+    let emission = shader.emission(fragment, uniforms);
+    if !state.lighting || shader.emissive() {
+        return Vector4::new(
+            color.x + emission.x,
+            color.y + emission.y,
+            color.z + emission.z,
+            color.w,
+        );
+    }
+
+    let mut diffuse = lighting::diffuse(
+        shader.normal(fragment, uniforms),
+        fragment.world_position,
+        uniforms.lights,
+        uniforms.occluders,
+        Twilight::from_params(uniforms.params),
+    );
+    if state.toon {
+        // Contorno solo en lo opaco: en las capas translúcidas sería un anillo
+        let to_camera = (uniforms.camera_position - fragment.world_position).normalized();
+        if state.blend_mode == BlendMode::Opaque
+            && fragment.normal.dot(to_camera) < lighting::TOON_OUTLINE
+        {
+            return Vector4::new(0.0, 0.0, 0.0, color.w);
+        }
+        diffuse = lighting::toon(diffuse);
+    }
+    Vector4::new(
+        color.x * diffuse.x + emission.x,
+        color.y * diffuse.y + emission.y,
+        color.z * diffuse.z + emission.z,
+        color.w,
+    )
+}
+
+fn render_points(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    vertices: &[VertexOutput],
+    shader: &dyn FragmentShader,
+    state: &RenderState,
+) {
+    let blend = state.blend_mode == BlendMode::Alpha;
+    for vertex in vertices {
+        let p = vertex.clip_position;
+        if p.w <= 0.0 || p.x.abs() > p.w || p.y.abs() > p.w || p.z.abs() > p.w {
+            continue;
+        }
+
+        let mut screen = viewport_transform(vertex, uniforms).screen_position;
+        screen.z += state.depth_bias;
+        let fragment = Fragment {
+            position: Vector2::new(screen.x, screen.y),
+            color: vertex.varyings.color,
+            depth: screen.z,
+            normal: vertex.varyings.normal,
+            tex_coords: vertex.varyings.tex_coords,
+            world_position: vertex.varyings.world_position,
+            object_position: vertex.varyings.object_position,
+            custom: vertex.varyings.custom,
+            coverage: u8::MAX,
+        };
+        let color = shade(shader, &fragment, uniforms, state);
+
+        // Los puntos más cercanos se dibujan más grandes
+        let size = (POINT_SIZE_SCALE / p.w).clamp(1.0, 4.0) as i32;
+        for dy in 0..size {
+            for dx in 0..size {
+                let x = screen.x as i32 + dx - size / 2;
+                let y = screen.y as i32 + dy - size / 2;
+                if blend {
+                    framebuffer.blend_with_coverage(x, y, screen.z, color, u8::MAX);
+                } else {
+                    framebuffer.point_with_depth(x, y, screen.z, rgb(color));
+                }
+            }
+        }
+    }
+}
+
+fn shade_fragments(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    shader: &dyn FragmentShader,
+    fragments: Vec<Fragment>,
+    state: &RenderState,
+) {
+    let blend = state.blend_mode == BlendMode::Alpha;
+    for frag in fragments {
+        let color = shade(shader, &frag, uniforms, state);
+        let x = frag.position.x as i32;
+        let y = frag.position.y as i32;
+        if blend {
+            framebuffer.blend_with_coverage(x, y, frag.depth, color, frag.coverage);
+        } else {
+            framebuffer.point_with_coverage(x, y, frag.depth, rgb(color), frag.coverage);
+        }
+    }
+}
+
 fn render_with_shader(
     framebuffer: &mut Framebuffer,
     uniforms: &Uniforms,
-    vertex_array: &[Vertex],
-    shader_fn: fn(&Vector3) -> Vector3,
+    mesh: &Obj,
+    shader: &dyn FragmentShader,
+    state: &RenderState,
+    tiled: bool,
 ) {
-    let transformed_vertices: Vec<Vertex> = vertex_array
+    framebuffer.set_depth_state(state.depth_func, state.depth_write);
+
+    // El vertex shader corre una sola vez por vértice único
+    let transformed_vertices: Vec<VertexOutput> = mesh
+        .vertices
         .iter()
         .map(|v| vertex_shader(v, uniforms))
         .collect();
 
-    for tri in transformed_vertices.chunks(3) {
+    if state.fill_mode == RenderMode::Points {
+        render_points(framebuffer, uniforms, &transformed_vertices, shader, state);
+        return;
+    }
+
+    // Ensamblado de triángulos: recorte, proyección a pantalla y culling
+    let mut screen_triangles: Vec<[VertexOutput; 3]> = Vec::new();
+    for tri in mesh.indices.chunks(3) {
         if tri.len() < 3 {
             continue;
         }
 
-        let fragments = triangle(&tri[0], &tri[1], &tri[2]);
-        for frag in fragments {
-            let color = shader_fn(&Vector3::new(frag.position.x, frag.position.y, frag.depth));
-            framebuffer.point(frag.position.x as i32, frag.position.y as i32, color);
+        let v0 = &transformed_vertices[tri[0] as usize];
+        let v1 = &transformed_vertices[tri[1] as usize];
+        let v2 = &transformed_vertices[tri[2] as usize];
+
+        for clipped in clip_triangle_against_frustum(v0, v1, v2) {
+            let mut screen_tri = clipped.map(|v| viewport_transform(&v, uniforms));
+            let back = is_backface(&screen_tri[0], &screen_tri[1], &screen_tri[2]);
+            let culled = match state.cull_mode {
+                CullMode::None => false,
+                CullMode::Back => back,
+                CullMode::Front => !back,
+            };
+            if culled {
+                continue;
+            }
+            if state.depth_bias != 0.0 || state.slope_depth_bias != 0.0 {
+                apply_depth_bias(&mut screen_tri, state);
+            }
+            screen_triangles.push(screen_tri);
+        }
+    }
+
+    if state.fill_mode == RenderMode::Wireframe {
+        stroke_edges(framebuffer, &screen_triangles, Vector3::one());
+        return;
+    }
+
+    rasterize_filled(
+        framebuffer,
+        uniforms,
+        shader,
+        &screen_triangles,
+        state,
+        tiled,
+    );
+
+    // Las aristas encima del relleno, con su propio depth bias
+    if state.fill_mode == RenderMode::FilledWireframe {
+        let overlay = RenderState {
+            depth_func: DepthFunc::LessEqual,
+            depth_write: false,
+            depth_bias: OVERLAY_WIRE_DEPTH_BIAS,
+            slope_depth_bias: OVERLAY_WIRE_SLOPE_BIAS,
+            ..*state
+        };
+        for screen_tri in &mut screen_triangles {
+            apply_depth_bias(screen_tri, &overlay);
+        }
+        framebuffer.set_depth_state(overlay.depth_func, overlay.depth_write);
+        stroke_edges(framebuffer, &screen_triangles, OVERLAY_WIRE_COLOR);
+    }
+}
+
+fn stroke_edges(
+    framebuffer: &mut Framebuffer,
+    screen_triangles: &[[VertexOutput; 3]],
+    color: Vector3,
+) {
+    for screen_tri in screen_triangles {
+        for i in 0..3 {
+            for frag in line(&screen_tri[i], &screen_tri[(i + 1) % 3]) {
+                framebuffer.point_with_depth(
+                    frag.position.x as i32,
+                    frag.position.y as i32,
+                    frag.depth,
+                    color,
+                );
+            }
+        }
+    }
+}
+
+// Polilínea en espacio de mundo con el estado de profundidad del draw (la
+// pendiente no aplica a una línea, solo `depth_bias`). Los tramos con un
+// extremo detrás de la cámara o muy fuera de pantalla se omiten en vez de
+// recortarse; con tramos cortos no se nota.
+fn draw_polyline(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    points: &[Vector3],
+    color: Vector3,
+    state: &RenderState,
+) {
+    let projected: Vec<Option<VertexOutput>> = points
+        .iter()
+        .map(|&point| {
+            let vertex = vertex_shader(
+                &Vertex::new(point, Vector3::up(), Vector2::zero()),
+                uniforms,
+            );
+            let p = vertex.clip_position;
+            let visible =
+                p.w > 0.0 && p.x.abs() <= 2.0 * p.w && p.y.abs() <= 2.0 * p.w && p.z.abs() <= p.w;
+            visible.then(|| viewport_transform(&vertex, uniforms))
+        })
+        .collect();
+
+    framebuffer.set_scissor(None);
+    framebuffer.set_stencil_state(StencilState::default());
+    framebuffer.set_depth_state(state.depth_func, state.depth_write);
+    for pair in projected.windows(2) {
+        if let [Some(a), Some(b)] = pair {
+            for frag in line(a, b) {
+                framebuffer.point_with_depth(
+                    frag.position.x as i32,
+                    frag.position.y as i32,
+                    frag.depth + state.depth_bias,
+                    color,
+                );
+            }
+        }
+    }
+}
+
+// Nombre del cuerpo centrado sobre su borde superior en pantalla, con una
+// sombra para que se lea sobre cualquier fondo
+fn draw_label(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    position: Vector3,
+    size: f32,
+    name: &str,
+    focal_length: f32,
+    color: Vector3,
+) {
+    let distance = position.distance_to(uniforms.camera_position);
+    let alpha = ((LABEL_FADE_END - distance) / (LABEL_FADE_END - LABEL_FADE_START)).clamp(0.0, 1.0);
+    let vertex = vertex_shader(
+        &Vertex::new(position, Vector3::up(), Vector2::zero()),
+        uniforms,
+    );
+    let p = vertex.clip_position;
+    if alpha <= 0.0 || p.w <= 0.0 || p.x.abs() > p.w || p.y.abs() > p.w {
+        return;
+    }
+
+    let center = viewport_transform(&vertex, uniforms).screen_position;
+    let radius = size * 0.5 * focal_length / p.w;
+    // Mismo tamaño en la ventana con cualquier factor de SSAA
+    let scale = 2 * framebuffer.ssaa_factor() as i32;
+    let x = center.x as i32 - text::text_width(name, scale) / 2;
+    let y = (center.y - radius) as i32 - (text::GLYPH_HEIGHT + 3) * scale;
+    let shadow = Vector4::new(0.0, 0.0, 0.0, alpha * 0.8);
+    text::draw_text(
+        framebuffer,
+        x + scale / 2,
+        y + scale / 2,
+        scale,
+        name,
+        shadow,
+    );
+    let color = Vector4::new(color.x, color.y, color.z, alpha);
+    text::draw_text(framebuffer, x, y, scale, name, color);
+}
+
+fn rasterize_filled(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    shader: &dyn FragmentShader,
+    screen_triangles: &[[VertexOutput; 3]],
+    state: &RenderState,
+    tiled: bool,
+) {
+    let perspective_correct = is_perspective(&uniforms.projection_matrix);
+    let samples = framebuffer.msaa_samples();
+
+    if tiled {
+        let mut binner = TileBinner::new(framebuffer.width, framebuffer.height);
+        for (index, screen_tri) in screen_triangles.iter().enumerate() {
+            binner.bin(index, screen_tri);
+        }
+
+        for tile in &binner.tiles {
+            for &index in &tile.triangles {
+                let screen_tri = &screen_triangles[index];
+                let fragments = triangle_in_rect(
+                    &screen_tri[0],
+                    &screen_tri[1],
+                    &screen_tri[2],
+                    perspective_correct,
+                    samples,
+                    &tile.rect,
+                );
+                shade_fragments(framebuffer, uniforms, shader, fragments, state);
+            }
         }
+        return;
+    }
+
+    // Sin tiles se rasteriza igual dentro del viewport, así los triángulos
+    // que solo llegan a la banda de guarda no recorren píxeles fuera de pantalla
+    let raster_rect = framebuffer.raster_rect();
+    for screen_tri in screen_triangles {
+        let fragments = triangle_in_rect(
+            &screen_tri[0],
+            &screen_tri[1],
+            &screen_tri[2],
+            perspective_correct,
+            samples,
+            &raster_rect,
+        );
+        shade_fragments(framebuffer, uniforms, shader, fragments, state);
     }
 }
 
+// Punto del plano de las órbitas (XY a la altura de `center`) bajo el cursor
+fn cursor_on_orbit_plane(
+    mouse: Vector2,
+    viewport: &Viewport,
+    camera: &Camera,
+    center: Vector3,
+) -> Option<Vector3> {
+    let direction = camera.ray(mouse, viewport);
+    let t = (center.z - camera.position.z) / direction.z;
+    (direction.z.abs() > 1e-6 && t > 0.0).then(|| camera.position + direction * t)
+}
+
+// Avisa una sola vez por nombre de los shaders que la escena pide y no están
+// registrados; esos cuerpos se dibujan con el de reemplazo
+fn warn_unknown_shaders(
+    registry: &ShaderRegistry,
+    bodies: &[CelestialBody],
+    belts: &[AsteroidBelt],
+    warned: &mut Vec<String>,
+) {
+    let mut warn = |name: &str, owner: &str| {
+        if registry.get(name).is_none() && !warned.iter().any(|known| known == name) {
+            eprintln!("⚠ Shader desconocido '{name}' en {owner}");
+            warned.push(name.to_string());
+        }
+    };
+    for belt in belts {
+        warn(&belt.shader, "un cinturón");
+    }
+    let mut pending: Vec<&CelestialBody> = bodies.iter().collect();
+    while let Some(body) = pending.pop() {
+        warn(&body.shader, &body.name);
+        for shell in &body.shells {
+            warn(&shell.shader, &body.name);
+        }
+        pending.extend(&body.children);
+    }
+}
+
+// Cada cuerpo con cola lleva sus partículas y un mesh que se rehace por frame
+struct Tail {
+    // Posición del cuerpo en `placed_bodies`
+    index: usize,
+    name: String,
+    particles: CometTail,
+    mesh: MeshHandle,
+}
+
+// Las colas de los cuerpos que siguen estando se conservan (con sus
+// partículas); las de los que ya no están liberan su mesh
+fn comet_tails(
+    renderer: &mut Renderer,
+    bodies: &[CelestialBody],
+    sun_position: Vector3,
+    mut previous: Vec<Tail>,
+) -> Vec<Tail> {
+    let graph = build_graph(bodies, sun_position, 0.0, None);
+    let placed = graph.placed_bodies();
+    let matched: Vec<(usize, Option<Tail>)> = placed
+        .iter()
+        .enumerate()
+        .filter(|(_, placed)| placed.body.tail)
+        .map(|(index, placed)| {
+            let found = previous
+                .iter()
+                .position(|tail| tail.name == placed.body.name);
+            (index, found.map(|found| previous.swap_remove(found)))
+        })
+        .collect();
+    for stale in previous {
+        renderer.remove_mesh(stale.mesh);
+    }
+    matched
+        .into_iter()
+        .map(|(index, kept)| match kept {
+            Some(tail) => Tail { index, ..tail },
+            None => Tail {
+                index,
+                name: placed[index].body.name.clone(),
+                particles: CometTail::new(index as u64),
+                mesh: renderer.add_mesh(Obj::points(Vec::new())),
+            },
+        })
+        .collect()
+}
+
 fn main() {
     let window_width = 900;
     let window_height = 600;
 
     let (mut window, thread) = raylib::init()
         .size(window_width, window_height)
+        .resizable()
         .title("🌌 Sistema Solar Procedural - Rust Renderer")
         .log_level(TraceLogLevel::LOG_WARNING)
         .build();
@@ -104,81 +711,1154 @@ fn main() {
     framebuffer.set_background_color(Vector3::new(0.02, 0.02, 0.05));
     framebuffer.init_texture(&mut window, &thread);
 
-    // Rotación de cámara y zoom
-    let mut rotation = Vector3::new(0.0, 0.0, 0.0);
-    let mut zoom = 1.0_f32;
+    let mut render_mode = RenderMode::Filled;
+    let mut show_stats = false;
+    let mut dither = false;
+    let mut show_orbits = true;
+    let mut show_labels = true;
+    let mut show_trojans = false;
 
-    // Ángulo de órbita (para animación de los planetas)
-    let mut orbit_angle: f32 = 0.0;
+    // Tiempo de simulación: espacio pausa, coma y punto cambian la velocidad
+    let mut clock = SimulationClock::new();
 
     // Cargar modelo de esfera
     let obj = Obj::load("assets/models/sphere.obj").expect("❌ No se pudo cargar sphere.obj");
-    let vertex_array = obj.get_vertex_array();
+    let mut renderer = Renderer::new();
+    // Anillo compartido por los cuerpos con `rings = true`, relativo a su escala
+    let ring = renderer.add_mesh(Obj::ring(0.7, 1.25, 128));
+    let sphere = renderer.add_mesh(obj);
+    let rock = renderer.add_mesh(Obj::rock(21, 0.35));
+    let speck = renderer.add_mesh(Obj::point());
+    // Icosaedro liso para los cuerpos que ocupan pocos píxeles
+    let low_poly = renderer.add_mesh(Obj::rock(0, 0.0));
+    let trojans = Trojans::generate(41, 120, 3.5);
+
+    let star_shader = StarShader::new(1);
+    let rocky_shader = RockyShader::new(2);
+    let gas_shader = GasShader::new(
+        3,
+        14.0,
+        0.5,
+        Vector3::new(0.55, 0.85, 1.0),
+        Vector3::new(0.2, 0.45, 0.8),
+    );
+    let giant_shader = GasShader::new(
+        8,
+        9.0,
+        0.8,
+        Vector3::new(0.95, 0.85, 0.7),
+        Vector3::new(0.65, 0.4, 0.25),
+    )
+    .with_storm(GreatStorm {
+        latitude: -0.35,
+        longitude: 1.0,
+        radius: 0.18,
+        color: Vector3::new(0.8, 0.35, 0.2),
+    });
+    // Gigante pálido de bandas suaves y sin tormenta, como Saturno
+    let pale_giant_shader = GasShader::new(
+        15,
+        12.0,
+        0.4,
+        Vector3::new(0.95, 0.88, 0.68),
+        Vector3::new(0.75, 0.62, 0.42),
+    );
+    // Con una imagen de la Tierra en assets se usa esa; si no, continentes procedurales
+    let earth_shader = match Texture::load("assets/textures/earth.png") {
+        Ok(texture) => EarthShader::new(4).with_albedo(texture),
+        Err(_) => EarthShader::new(4),
+    };
+    let ice_shader = IceShader::new(5);
+    let moon_shader = MoonShader::new(10);
+    // Planeta cuyo shader se edita en caliente desde un archivo de texto
+    let mut script_shader = ScriptShader::load("assets/shaders/custom.shader", 12);
+    // Fondo: seis imágenes en assets/skybox si existen; si no, estrellas procedurales
+    let skybox_shader = match Cubemap::load([
+        "assets/skybox/px.png",
+        "assets/skybox/nx.png",
+        "assets/skybox/py.png",
+        "assets/skybox/ny.png",
+        "assets/skybox/pz.png",
+        "assets/skybox/nz.png",
+    ]) {
+        Ok(cubemap) => SkyboxShader::new(cubemap),
+        Err(_) => SkyboxShader::star_field(13, 256),
+    };
+    // Encima del fondo se suman nebulosas y galaxias lejanas
+    let nebula_shader = NebulaShader::new(31, 128);
+    let galaxy_shader = GalaxyShader::new(32, 6);
+    let background = LayeredShader::new(&skybox_shader)
+        .with_layer(&nebula_shader, LayerBlend::Add, 1.0)
+        .with_layer(&galaxy_shader, LayerBlend::Add, 1.0);
+    let tail_shader = TailShader;
+    let outline_shader = SolidShader {
+        color: OUTLINE_COLOR,
+    };
+    // Gris neutro e iluminado para los nombres de shader que no existen
+    let unknown_shader = SolidShader {
+        color: Vector3::new(0.6, 0.6, 0.6),
+    };
+    let mut unknown_shaders: Vec<String> = Vec::new();
+    let ring_shader = RingShader::new(9, Vector3::new(0.9, 0.82, 0.68));
+    let cloud_shader = CloudShader::new(7, 0.45);
+    let earth_atmosphere =
+        AtmosphereShader::new(Vector3::new(0.35, 0.6, 1.0), 3.0).with_aurora(Aurora::new(14, 0.35));
+    let gas_atmosphere = AtmosphereShader::new(Vector3::new(0.5, 0.85, 1.0), 2.5);
+    let thin_atmosphere = AtmosphereShader::new(Vector3::new(0.9, 0.55, 0.4), 5.0);
+    let lava_shader = LavaShader::new(6, 6.0, Vector3::new(1.0, 0.35, 0.05));
+    // La Tierra se arma por capas: superficie y nubes en el mismo fragmento.
+    // La atmósfera sigue en su propia esfera porque el halo sobresale de la silueta.
+    let layered_earth =
+        LayeredShader::new(&earth_shader).with_layer(&cloud_shader, LayerBlend::Normal, 1.0);
+
+    // La escena conserva la proporción inicial aunque cambie la ventana
+    let scene_aspect = window_width as f32 / window_height as f32;
 
     // Propiedades iniciales del sistema
-    let sun_position = Vector3::new(450.0, 300.0, 0.0);
+    let sun_position = Vector3::new(0.0, 0.0, 0.0);
 
-    let mut rocky_orbit_radius = 200.0;
-    let mut gas_orbit_radius = 320.0;
+    // Cámara orbitando alrededor del sol; arranca a la distancia en la que
+    // una unidad del mundo equivale a ~1 píxel
+    let mut camera = Camera::new(sun_position, sun_position, PI / 4.0);
+    camera.set_orbit(0.0, 0.0, camera.focal_length(window_height as f32));
+    // R: orbitar alrededor del cuerpo seleccionado y después perseguirlo;
+    // Tab: vuelo libre
+    let mut camera_mode = CameraMode::System;
+    let mut system_distance = camera.orbit_angles().2;
+    // El cuerpo que la cámara ya enfocó, para acercarse solo al cambiar
+    let mut framed: Option<usize> = None;
+    let mut chase = Chase::new(1.0);
+    let mut bookmarks = Bookmarks::load(BOOKMARKS_PATH);
+    let mut transition: Option<Transition> = None;
+    // Los controles mueven `camera`; lo que se ve es esta, que la alcanza
+    // suavemente
+    let mut shown = SmoothCamera::new(camera);
+
+    // Cada cuerpo es un dato del archivo de escena (el primer argumento, o el
+    // sistema por defecto): agregar un planeta es agregar una entrada. Los
+    // shaders se nombran como en el registro; N elige un cuerpo y V le
+    // cambia el shader
+    // `--seed N` genera un sistema al azar en vez de leer un archivo,
+    // `--solar` carga el Sistema Solar real y `--resume [archivo]` sigue una
+    // partida guardada
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut system_seed = match args.as_slice() {
+        [flag, seed] if flag == "--seed" => seed.parse().ok(),
+        _ => None,
+    };
+    let scene = match system_seed {
+        Some(seed) => generator::generate_system(seed),
+        None if args.first().is_some_and(|arg| arg == "--solar") => scene::solar_system(),
+        None if args.first().is_some_and(|arg| arg == "--resume") => {
+            let save_path = args.get(1).map_or(SAVE_PATH, String::as_str);
+            scene::load(save_path).unwrap_or_else(|e| {
+                eprintln!("❌ No se pudo cargar la partida: {e}");
+                scene::load(DEFAULT_SCENE).expect("❌ No se pudo cargar la escena")
+            })
+        }
+        None => {
+            let scene_path = args.first().map_or(DEFAULT_SCENE, String::as_str);
+            scene::load(scene_path).expect("❌ No se pudo cargar la escena")
+        }
+    };
+    let mut bodies = scene.bodies;
+    let mut belts = scene.belts;
+    let mut probe_route = scene.probe;
+    let mut event_settings = scene.events;
+    let mut events = EventRegistry::from_settings(&event_settings);
+    // H: lanzar la sonda; U: viajar a bordo
+    let mut probe: Option<Probe> = None;
+    let mut ride_probe = false;
+    let mut tails = comet_tails(&mut renderer, &bodies, sun_position, Vec::new());
+    let mut debris = ParticleSystem::new(0);
+    let debris_mesh = renderer.add_mesh(Obj::points(Vec::new()));
+    let mut selected_body = 0;
+    // F: gravedad entre todos los cuerpos en vez de órbitas fijas
+    let mut gravity: Option<NBody> = None;
+    // Planetas agregados con B, para numerarlos
+    let mut spawned = 0;
+    // Estado de una partida guardada, que se aplica al empezar el frame
+    let mut resume = scene.snapshot;
 
     while !window.window_should_close() {
+        script_shader.reload_if_changed();
+        let registry = ShaderRegistry::new()
+            .with("star", &star_shader)
+            .with("rocky", &rocky_shader)
+            .with("gas", &gas_shader)
+            .with("giant", &giant_shader)
+            .with("pale_giant", &pale_giant_shader)
+            .with("earth", &layered_earth)
+            .with("ice", &ice_shader)
+            .with("lava", &lava_shader)
+            .with("moon", &moon_shader)
+            .with("custom", &script_shader)
+            .with("clouds", &cloud_shader)
+            .with("earth_atmosphere", &earth_atmosphere)
+            .with("gas_atmosphere", &gas_atmosphere)
+            .with("thin_atmosphere", &thin_atmosphere)
+            .with_fallback(&unknown_shader);
+        warn_unknown_shaders(&registry, &bodies, &belts, &mut unknown_shaders);
+
+        if window.is_window_resized() {
+            framebuffer.resize(
+                window.get_screen_width() as u32,
+                window.get_screen_height() as u32,
+            );
+            framebuffer.init_texture(&mut window, &thread);
+        }
+
         // --- Controles de cámara ---
-        if window.is_key_down(KeyboardKey::KEY_LEFT) {
-            rotation.y -= PI / 180.0 * 2.0;
+        // R: orbitar el cuerpo seleccionado, perseguirlo y volver al sistema
+        // con la distancia de antes. Tab: vuelo libre, o de vuelta al sistema.
+        let requested = if window.is_key_pressed(KeyboardKey::KEY_R) {
+            Some(match camera_mode {
+                CameraMode::Body => CameraMode::Follow,
+                CameraMode::Follow => CameraMode::System,
+                _ => CameraMode::Body,
+            })
+        } else if window.is_key_pressed(KeyboardKey::KEY_TAB) {
+            Some(match camera_mode {
+                CameraMode::Free => CameraMode::System,
+                _ => CameraMode::Free,
+            })
+        } else {
+            None
+        };
+        if let Some(mode) = requested {
+            camera.up = Vector3::up();
+            if camera_mode == CameraMode::System {
+                system_distance = camera.orbit_angles().2;
+            }
+            if mode == CameraMode::System {
+                let (yaw, pitch, _) = camera.orbit_angles();
+                camera.target = sun_position;
+                camera.set_orbit(yaw, pitch, system_distance);
+            }
+            if mode == CameraMode::Free {
+                window.disable_cursor();
+            } else {
+                window.enable_cursor();
+            }
+            framed = None;
+            camera_mode = mode;
         }
-        if window.is_key_down(KeyboardKey::KEY_RIGHT) {
-            rotation.y += PI / 180.0 * 2.0;
+        // Ctrl+1..9 guarda la cámara; 1..9 vuelve a ella orbitando el sistema
+        let control = window.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || window.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        let digits = [
+            KeyboardKey::KEY_ONE,
+            KeyboardKey::KEY_TWO,
+            KeyboardKey::KEY_THREE,
+            KeyboardKey::KEY_FOUR,
+            KeyboardKey::KEY_FIVE,
+            KeyboardKey::KEY_SIX,
+            KeyboardKey::KEY_SEVEN,
+            KeyboardKey::KEY_EIGHT,
+            KeyboardKey::KEY_NINE,
+        ];
+        for (index, key) in digits.into_iter().enumerate() {
+            let slot = index + 1;
+            if !window.is_key_pressed(key) {
+                continue;
+            }
+            if control {
+                bookmarks.set(slot, camera);
+                match bookmarks.save(BOOKMARKS_PATH) {
+                    Ok(()) => println!("📷 Cámara {slot} guardada"),
+                    Err(e) => eprintln!("❌ No se pudo guardar la cámara: {e}"),
+                }
+            } else if let Some(saved) = bookmarks.get(slot) {
+                transition = Some(Transition::new(camera, saved, BOOKMARK_TRANSITION));
+                camera_mode = CameraMode::System;
+                framed = None;
+                window.enable_cursor();
+            }
         }
-        if window.is_key_down(KeyboardKey::KEY_UP) {
-            rotation.x -= PI / 180.0 * 2.0;
+        if let Some(active) = &mut transition {
+            camera = active.advance(window.get_frame_time());
+            if active.finished() {
+                transition = None;
+            }
         }
-        if window.is_key_down(KeyboardKey::KEY_DOWN) {
-            rotation.x += PI / 180.0 * 2.0;
+        // En vuelo libre las flechas miran y WASD/QE mueven; si no, las
+        // flechas orbitan y A/S acercan
+        let flying = camera_mode == CameraMode::Free;
+        let key_axis = |positive, negative| {
+            f32::from(u8::from(window.is_key_down(positive)))
+                - f32::from(u8::from(window.is_key_down(negative)))
+        };
+        let turn = ORBIT_SPEED * window.get_frame_time();
+        let yaw = key_axis(KeyboardKey::KEY_RIGHT, KeyboardKey::KEY_LEFT) * turn;
+        let pitch = key_axis(KeyboardKey::KEY_DOWN, KeyboardKey::KEY_UP) * turn;
+        if flying {
+            let mouse = window.get_mouse_delta() * MOUSE_SENSITIVITY;
+            camera.look(-yaw - mouse.x, -pitch - mouse.y);
+            let boost = if window.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) {
+                FLY_BOOST
+            } else {
+                1.0
+            };
+            let step = FLY_SPEED * boost * window.get_frame_time();
+            camera.fly(
+                key_axis(KeyboardKey::KEY_D, KeyboardKey::KEY_A) * step,
+                key_axis(KeyboardKey::KEY_E, KeyboardKey::KEY_Q) * step,
+                key_axis(KeyboardKey::KEY_W, KeyboardKey::KEY_S) * step,
+            );
+        } else {
+            let zoom = (key_axis(KeyboardKey::KEY_S, KeyboardKey::KEY_A)
+                * ZOOM_SPEED
+                * window.get_frame_time())
+            .exp();
+            if camera_mode == CameraMode::Follow {
+                chase.turn(yaw, pitch);
+                chase.zoom(zoom);
+            } else {
+                camera.orbit(yaw, pitch);
+                camera.zoom(zoom);
+            }
         }
 
-        if window.is_key_down(KeyboardKey::KEY_A) {
-            zoom *= 1.02;
+        // --- Modo de render ---
+        if !flying && window.is_key_pressed(KeyboardKey::KEY_W) {
+            render_mode = match render_mode {
+                RenderMode::Wireframe => RenderMode::Filled,
+                _ => RenderMode::Wireframe,
+            };
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_O) {
+            render_mode = match render_mode {
+                RenderMode::FilledWireframe => RenderMode::Filled,
+                _ => RenderMode::FilledWireframe,
+            };
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_M) {
+            let samples = match framebuffer.msaa_samples() {
+                1 => 2,
+                2 => 4,
+                _ => 1,
+            };
+            framebuffer.set_msaa_samples(samples);
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_X) {
+            let factor = match framebuffer.ssaa_factor() {
+                1 => 2,
+                2 => 3,
+                3 => 4,
+                _ => 1,
+            };
+            framebuffer.set_ssaa_factor(factor);
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_I) {
+            show_stats = !show_stats;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_Z) {
+            renderer.depth_prepass = !renderer.depth_prepass;
+        }
+        if !flying && window.is_key_pressed(KeyboardKey::KEY_D) {
+            dither = !dither;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_C) {
+            renderer.toon = !renderer.toon;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_T) {
+            renderer.tiled = !renderer.tiled;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_N) {
+            selected_body = (selected_body + 1) % count_bodies(&bodies);
+            if let Some(body) = body_mut(&mut bodies, selected_body) {
+                println!("🪐 {}: {}", body.name, body.shader);
+            }
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_V)
+            && let Some(body) = body_mut(&mut bodies, selected_body)
+        {
+            body.shader = registry.next_name(&body.shader).to_string();
+            println!("🎨 {}: {}", body.name, body.shader);
+        }
+        // G: otro sistema procedural con una semilla nueva
+        if window.is_key_pressed(KeyboardKey::KEY_G) {
+            let seed = system_seed.map_or_else(rand::random, |seed: u64| seed.wrapping_add(1));
+            println!("🎲 Sistema generado con la semilla {seed}");
+            let scene = generator::generate_system(seed);
+            bodies = scene.bodies;
+            belts = scene.belts;
+            probe_route = scene.probe;
+            probe = None;
+            event_settings = scene.events;
+            events = EventRegistry::from_settings(&event_settings);
+            tails = comet_tails(&mut renderer, &bodies, sun_position, tails);
+            system_seed = Some(seed);
+            selected_body = 0;
+            if gravity.is_some() {
+                gravity = Some(NBody::from_orbits(&bodies, sun_position, clock.time));
+            }
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_F) {
+            gravity = match gravity {
+                Some(_) => None,
+                None => Some(NBody::from_orbits(&bodies, sun_position, clock.time)),
+            };
+            println!(
+                "🪐 Gravedad: {}",
+                if gravity.is_some() { "sí" } else { "no" }
+            );
+        }
+        // K/J: empujar o frenar al cuerpo seleccionado para perturbar el sistema
+        if let Some(gravity) = &mut gravity {
+            if window.is_key_pressed(KeyboardKey::KEY_K) {
+                gravity.kick(selected_body, 1.15);
+            }
+            if window.is_key_pressed(KeyboardKey::KEY_J) {
+                gravity.kick(selected_body, 0.85);
+            }
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_L) {
+            show_orbits = !show_orbits;
+        }
+        // E: etiquetas con el nombre de cada cuerpo
+        if !flying && window.is_key_pressed(KeyboardKey::KEY_E) {
+            show_labels = !show_labels;
         }
-        if window.is_key_down(KeyboardKey::KEY_S) {
-            zoom *= 0.98;
+        // Y: troyanos del gigante en sus puntos L4 y L5
+        if window.is_key_pressed(KeyboardKey::KEY_Y) {
+            show_trojans = !show_trojans;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_H) {
+            probe = match probe {
+                Some(_) => None,
+                None => {
+                    let route = probe_route
+                        .clone()
+                        .unwrap_or_else(|| ProbeSettings::grand_tour(&bodies));
+                    println!("🛰 Sonda: {}", route.route.join(" → "));
+                    Some(Probe::new(route))
+                }
+            };
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_U) {
+            ride_probe = !ride_probe;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_F5) {
+            let snapshot = Snapshot {
+                time: clock.time,
+                time_scale: clock.scale,
+                paused: clock.paused,
+                camera: Some(camera),
+                camera_mode,
+                chase: Some(chase.offset),
+                seed: system_seed,
+                selected: selected_body,
+                gravity: gravity
+                    .as_ref()
+                    .map(|gravity| (gravity.positions.clone(), gravity.velocities().to_vec())),
+            };
+            match scene::save(
+                SAVE_PATH,
+                &bodies,
+                &belts,
+                probe_route.as_ref(),
+                &event_settings,
+                &snapshot,
+            ) {
+                Ok(()) => println!("💾 Partida guardada en {SAVE_PATH}"),
+                Err(e) => eprintln!("❌ No se pudo guardar la partida: {e}"),
+            }
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_F9) {
+            match scene::load(SAVE_PATH) {
+                Ok(scene) => {
+                    bodies = scene.bodies;
+                    belts = scene.belts;
+                    probe_route = scene.probe;
+                    probe = None;
+                    event_settings = scene.events;
+                    events = EventRegistry::from_settings(&event_settings);
+                    tails = comet_tails(&mut renderer, &bodies, sun_position, tails);
+                    resume = scene.snapshot;
+                    println!("💾 Partida cargada de {SAVE_PATH}");
+                }
+                Err(e) => eprintln!("❌ No se pudo cargar la partida: {e}"),
+            }
+        }
+        if let Some(snapshot) = resume.take() {
+            clock.time = snapshot.time;
+            clock.set_scale(snapshot.time_scale);
+            clock.paused = snapshot.paused;
+            system_seed = snapshot.seed;
+            selected_body = snapshot
+                .selected
+                .min(count_bodies(&bodies).saturating_sub(1));
+            // La cámara vuelve tal cual, sin transición ni reencuadre
+            if let Some(saved) = snapshot.camera {
+                camera = saved;
+            }
+            if let Some(offset) = snapshot.chase {
+                chase.offset = offset;
+            }
+            camera_mode = snapshot.camera_mode;
+            framed = matches!(camera_mode, CameraMode::Body | CameraMode::Follow)
+                .then_some(selected_body);
+            if camera_mode == CameraMode::Free {
+                window.disable_cursor();
+            } else {
+                window.enable_cursor();
+            }
+            transition = None;
+            shown = SmoothCamera::new(camera);
+            gravity = snapshot
+                .gravity
+                .and_then(|(positions, velocities)| NBody::resume(&bodies, positions, velocities));
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_SPACE) {
+            clock.paused = !clock.paused;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_PERIOD) {
+            clock.faster();
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_COMMA) {
+            clock.slower();
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_P) {
+            render_mode = match render_mode {
+                RenderMode::Points => RenderMode::Filled,
+                _ => RenderMode::Points,
+            };
+        }
+
+        // B: un planeta al azar en la órbita que pasa por el cursor
+        if window.is_key_pressed(KeyboardKey::KEY_B) {
+            let viewport =
+                Viewport::letterboxed(framebuffer.width, framebuffer.height, scene_aspect);
+            let mouse = window.get_mouse_position() * framebuffer.ssaa_factor() as f32;
+            match cursor_on_orbit_plane(mouse, &viewport, &shown.camera, sun_position) {
+                Some(point) => {
+                    spawned += 1;
+                    let target = point - sun_position;
+                    let mut planet = generator::random_planet(
+                        rand::random(),
+                        format!("Nuevo {spawned}"),
+                        target.length(),
+                    );
+                    // Con la fase que lo deja justo bajo el cursor
+                    if let Some(orbit) = &mut planet.orbit {
+                        let current = orbit.offset(clock.time);
+                        orbit.periapsis += target.y.atan2(target.x) - current.y.atan2(current.x);
+                    }
+                    if let Some(gravity) = &mut gravity {
+                        let offset = planet
+                            .orbit
+                            .map_or(Vector3::zero(), |orbit| orbit.offset(clock.time));
+                        gravity.add(&planet, sun_position + offset, clock.time);
+                    }
+                    println!("🪐 {}: {}", planet.name, planet.shader);
+                    selected_body = count_bodies(&bodies);
+                    bodies.push(planet);
+                    tails = comet_tails(&mut renderer, &bodies, sun_position, tails);
+                }
+                None => println!("El cursor no apunta al plano de las órbitas"),
+            }
+        }
+        // Retroceso: borrar el cuerpo seleccionado con sus lunas
+        if window.is_key_pressed(KeyboardKey::KEY_BACKSPACE)
+            && count_bodies(&bodies) > 1
+            && let Some(removed) = remove_body(&mut bodies, selected_body)
+        {
+            if let Some(gravity) = &mut gravity {
+                let removed_count = count_bodies(std::slice::from_ref(&removed));
+                gravity.remove(selected_body..selected_body + removed_count);
+            }
+            println!("🗑 {}", removed.name);
+            selected_body = selected_body.min(count_bodies(&bodies) - 1);
+            tails = comet_tails(&mut renderer, &bodies, sun_position, tails);
         }
 
         // --- Actualizar órbita ---
-        orbit_angle += PI / 180.0 * 0.5; // velocidad orbital
-        let rocky_pos = Vector3::new(
-            sun_position.x + rocky_orbit_radius * orbit_angle.cos(),
-            sun_position.y + rocky_orbit_radius * orbit_angle.sin(),
-            0.0,
+        // Las partículas avanzan con el tiempo escalado, pero sin pasos tan
+        // grandes que a x1000 cada frame emita miles
+        let previous_angle = clock.time;
+        let dt = clock.advance(window.get_frame_time()).min(0.1);
+        let orbit_angle = clock.time;
+        // Eventos de la escena: pueden cambiar el reloj, órbitas o sumar cuerpos
+        let count = count_bodies(&bodies);
+        let added = bodies.len();
+        events.run(
+            &mut World {
+                bodies: &mut bodies,
+                clock: &mut clock,
+            },
+            previous_angle,
+            orbit_angle,
         );
-        let gas_pos = Vector3::new(
-            sun_position.x + gas_orbit_radius * (orbit_angle * 0.7).cos(),
-            sun_position.y + gas_orbit_radius * (orbit_angle * 0.7).sin(),
-            0.0,
+        if count_bodies(&bodies) != count {
+            if let Some(gravity) = &mut gravity {
+                for body in &bodies[added..] {
+                    let offset = body
+                        .orbit
+                        .map_or(Vector3::zero(), |orbit| orbit.offset(orbit_angle));
+                    gravity.add(body, sun_position + offset, orbit_angle);
+                }
+            }
+            tails = comet_tails(&mut renderer, &bodies, sun_position, tails);
+        }
+        if let Some(gravity) = &mut gravity {
+            let merges = gravity.step(orbit_angle - previous_angle);
+            for merge in &merges {
+                // El árbol de cuerpos sigue a la simulación: el sobreviviente
+                // crece y el absorbido deja a sus lunas en su lugar
+                if let Some(survivor) = body_mut(&mut bodies, merge.survivor) {
+                    survivor.scale = merge.radius / SPHERE_RADIUS;
+                    println!("💥 {} absorbió un cuerpo", survivor.name);
+                }
+                dissolve_body(&mut bodies, merge.absorbed);
+                selected_body = match selected_body.cmp(&merge.absorbed) {
+                    Ordering::Less => selected_body,
+                    Ordering::Equal => {
+                        merge.survivor - usize::from(merge.survivor > merge.absorbed)
+                    }
+                    Ordering::Greater => selected_body - 1,
+                };
+                debris.emit(
+                    DEBRIS_PARTICLES,
+                    1.0,
+                    merge.position,
+                    merge.velocity * RADIANS_PER_SECOND,
+                    20.0 + merge.radius,
+                    DEBRIS_LIFETIME,
+                );
+            }
+            if !merges.is_empty() {
+                tails = comet_tails(&mut renderer, &bodies, sun_position, tails);
+            }
+        }
+        debris.update(dt);
+        renderer.replace_mesh(debris_mesh, debris.mesh(Vector3::new(1.0, 0.65, 0.35)));
+        let simulated = gravity.as_ref().map(|gravity| gravity.positions.as_slice());
+        let graph = build_graph(&bodies, sun_position, orbit_angle, simulated);
+        let placed = graph.placed_bodies();
+        // Cada cuerpo con luz ilumina desde donde está en este frame; una
+        // escena sin estrellas declaradas se ilumina desde el centro
+        let mut lights: Vec<Light> = placed
+            .iter()
+            .filter_map(|placed| {
+                placed.body.light.map(|color| Light {
+                    position: placed.position,
+                    color,
+                    radius: placed.radius(),
+                })
+            })
+            .collect();
+        if lights.is_empty() {
+            lights.push(Light {
+                position: sun_position,
+                color: Vector3::one(),
+                radius: 0.0,
+            });
+        }
+        // Los troyanos acompañan al planeta más grande que orbita la estrella
+        let trojan_host = placed
+            .iter()
+            .filter(|placed| placed.body.light.is_none() && placed.body.orbit.is_some())
+            .filter(|placed| bodies.iter().any(|body| std::ptr::eq(body, placed.body)))
+            .max_by(|a, b| a.body.scale.total_cmp(&b.body.scale))
+            .filter(|_| show_trojans)
+            .map(|host| {
+                let star = lighting::nearest_light(&lights, host.position);
+                (star, host.position)
+            });
+        // Todo lo que no brilla puede tapar a una estrella
+        let occluders: Vec<Occluder> = placed
+            .iter()
+            .filter(|placed| placed.body.light.is_none())
+            .map(|placed| Occluder {
+                center: placed.position,
+                radius: placed.radius(),
+            })
+            .collect();
+        for tail in &mut tails {
+            let nucleus = placed[tail.index].position;
+            let star = lighting::nearest_light(&lights, nucleus);
+            tail.particles.update(nucleus, star, dt);
+            renderer.replace_mesh(tail.mesh, tail.particles.mesh());
+        }
+        if let Some(target) = placed.get(selected_body) {
+            let reframe = framed != Some(selected_body);
+            match camera_mode {
+                CameraMode::Body if reframe => camera.frame(target.position, target.radius()),
+                CameraMode::Body => camera.retarget(target.position),
+                CameraMode::Follow => {
+                    if reframe {
+                        chase = Chase::new(target.radius());
+                    }
+                    let star = lighting::nearest_light(&lights, target.position);
+                    chase.place(&mut camera, target.position, star);
+                }
+                CameraMode::System | CameraMode::Free => {}
+            }
+            if matches!(camera_mode, CameraMode::Body | CameraMode::Follow) {
+                framed = Some(selected_body);
+            }
+        }
+        shown.follow(&camera, camera_mode, window.get_frame_time());
+        // La sonda sigue los puntos de paso de este frame; en pausa se queda
+        let waypoints = probe
+            .as_ref()
+            .map_or(Vec::new(), |probe| probe.waypoints(&placed));
+        if let Some(probe) = &mut probe {
+            let seconds = if clock.paused {
+                0.0
+            } else {
+                window.get_frame_time()
+            };
+            probe.advance(&waypoints, seconds);
+        }
+        // A bordo, la cámara va detrás de la sonda mirando hacia donde avanza
+        let view = match probe.as_ref().filter(|_| ride_probe) {
+            Some(probe) => {
+                let up = Vector3::new(0.0, 0.0, 1.0);
+                let eye = probe.position - probe.heading * RIDE_BEHIND + up * RIDE_ABOVE;
+                let target = probe.position + probe.heading * RIDE_BEHIND;
+                Camera::new(eye, target, camera.fov_y).with_up(up)
+            }
+            None => shown.camera,
+        };
+        let camera_position = view.position;
+        let view_matrix = view.view_matrix();
+
+        // El viewport sigue la resolución interna (cambia con SSAA y al
+        // redimensionar) y se centra con la proporción de la escena
+        let viewport = Viewport::letterboxed(framebuffer.width, framebuffer.height, scene_aspect);
+        framebuffer.set_viewport(viewport);
+        let projection_matrix = view.projection_matrix(viewport.aspect());
+        let viewport_matrix = create_viewport_matrix(
+            viewport.x as f32,
+            viewport.y as f32,
+            viewport.width as f32,
+            viewport.height as f32,
         );
 
+        framebuffer.set_dither(dither);
         framebuffer.clear();
+        let mut commands = CommandBuffer::new(
+            view_matrix,
+            projection_matrix,
+            viewport_matrix,
+            &lights,
+            &occluders,
+            clock.seconds(),
+        );
 
-        // --- Render del Sol ---
-        let sun_matrix = create_model_matrix(sun_position, 185.0 * zoom, rotation);
-        let uniforms = Uniforms {
-            model_matrix: sun_matrix,
-        };
-        render_with_shader(&mut framebuffer, &uniforms, &vertex_array, star_shader);
+        // El fondo va primero y sin escribir profundidad, así todo lo demás
+        // queda encima. Se ve desde adentro: se descartan las caras frontales.
+        commands.draw(DrawCommand {
+            mesh: sphere,
+            model_matrix: create_model_matrix(camera_position, 4000.0, Vector3::zero()),
+            shader: &background,
+            params: &NO_PARAMS,
+            seed: 0,
+            state: RenderState {
+                cull_mode: CullMode::Front,
+                depth_write: false,
+                ..RenderState::default()
+            },
+            scissor: None,
+            stencil: StencilState::default(),
+        });
 
-        // --- Planeta rocoso ---
-        let rocky_matrix = create_model_matrix(rocky_pos, 25.0 * zoom, rotation);
-        let uniforms = Uniforms {
-            model_matrix: rocky_matrix,
+        // Superficies, nubes y anillos salen del grafo con la matriz de mundo
+        // ya compuesta. Todos los cuerpos comparten la esfera: un solo draw
+        // instanciado, y cada uno lleva su propia semilla. Los que se ven muy
+        // chicos (planetas enanos lejanos) van aparte con el icosaedro.
+        let focal_length = view.focal_length(viewport.height as f32);
+        let mut surfaces = Vec::new();
+        let mut distant_surfaces = Vec::new();
+        let selected = placed.get(selected_body);
+        let mut selected_surface = Vec::new();
+        let mut shells = Vec::new();
+        let mut rings = Vec::new();
+        graph.traverse(Matrix::identity(), &mut |node, world| match node.content {
+            Some(NodeContent::Surface(body)) => {
+                let position = Vector3::new(world.m12, world.m13, world.m14);
+                let pixels = body.scale * SPHERE_RADIUS * focal_length
+                    / position.distance_to(camera_position);
+                let instance = Instance {
+                    model_matrix: world,
+                    shader: registry.resolve(&body.shader),
+                    params: Some(&body.params),
+                    seed: Some(body.seed),
+                };
+                if selected.is_some_and(|selected| std::ptr::eq(selected.body, body)) {
+                    selected_surface.push(instance);
+                } else if pixels < LOW_DETAIL_PIXELS {
+                    distant_surfaces.push(instance);
+                } else {
+                    surfaces.push(instance);
+                }
+            }
+            // Una capa con un shader desconocido se omite: opaca taparía al cuerpo
+            Some(NodeContent::Shell(body, shell)) => {
+                if let Some(shader) = registry.get(&shell.shader) {
+                    shells.push(Instance {
+                        model_matrix: world,
+                        shader: Some(shader),
+                        params: None,
+                        seed: Some(body.seed),
+                    });
+                }
+            }
+            Some(NodeContent::Rings(body)) => rings.push(Instance {
+                model_matrix: world,
+                shader: None,
+                params: None,
+                seed: Some(body.seed),
+            }),
+            None => {}
+        });
+        let surface_command = DrawCommand {
+            mesh: sphere,
+            model_matrix: Matrix::identity(),
+            shader: &star_shader,
+            params: &NO_PARAMS,
+            seed: 0,
+            state: RenderState {
+                fill_mode: render_mode,
+                ..RenderState::default()
+            },
+            scissor: None,
+            stencil: StencilState::default(),
         };
-        render_with_shader(&mut framebuffer, &uniforms, &vertex_array, rocky_shader);
+        commands.draw_instanced(surface_command, &surfaces);
+        commands.draw_instanced(
+            DrawCommand {
+                mesh: low_poly,
+                ..surface_command
+            },
+            &distant_surfaces,
+        );
+        commands.draw_instanced(
+            DrawCommand {
+                stencil: StencilState {
+                    func: StencilFunc::Always,
+                    reference: SELECTED_STENCIL,
+                    pass_op: StencilOp::Replace,
+                },
+                ..surface_command
+            },
+            &selected_surface,
+        );
 
-        // --- Planeta gaseoso ---
-        let gas_matrix = create_model_matrix(gas_pos, 60.0 * zoom, rotation);
+        // Nubes y atmósferas: esferas apenas más grandes, translúcidas y sin
+        // escribir profundidad
+        commands.draw_instanced(
+            DrawCommand {
+                mesh: sphere,
+                model_matrix: Matrix::identity(),
+                shader: &cloud_shader,
+                params: &NO_PARAMS,
+                seed: 0,
+                state: RenderState {
+                    blend_mode: BlendMode::Alpha,
+                    depth_write: false,
+                    fill_mode: render_mode,
+                    ..RenderState::default()
+                },
+                scissor: None,
+                stencil: StencilState::default(),
+            },
+            &shells,
+        );
+        // Translúcido solo para que no entre en la pasada de profundidad y se
+        // dibuje después de las atmósferas del cuerpo. En alambre o puntos el
+        // cuerpo casi no marca el stencil y el contorno lo taparía.
+        let filled = matches!(
+            render_mode,
+            RenderMode::Filled | RenderMode::FilledWireframe
+        );
+        if let Some(selected) = selected.filter(|_| filled) {
+            commands.draw(DrawCommand {
+                mesh: sphere,
+                model_matrix: create_model_matrix(
+                    selected.position,
+                    selected.body.scale * OUTLINE_SCALE,
+                    Vector3::zero(),
+                ),
+                shader: &outline_shader,
+                params: &NO_PARAMS,
+                seed: 0,
+                state: RenderState {
+                    blend_mode: BlendMode::Alpha,
+                    depth_write: false,
+                    lighting: false,
+                    ..RenderState::default()
+                },
+                scissor: None,
+                stencil: StencilState {
+                    func: StencilFunc::NotEqual,
+                    reference: SELECTED_STENCIL,
+                    pass_op: StencilOp::Keep,
+                },
+            });
+        }
+
+        // Cinturones: rocas de pocos polígonos, y un solo punto para las que
+        // quedan a menos de un píxel de radio
+        // Cada roca como posición, escala y giro; los troyanos se dibujan
+        // igual que un cinturón más
+        let mut swarms: Vec<(&str, Vec<_>)> = belts
+            .iter()
+            .map(|belt| {
+                let rocks = belt.asteroids.iter().map(|asteroid| {
+                    (
+                        sun_position + asteroid.position(orbit_angle),
+                        asteroid.scale,
+                        asteroid.rotation(orbit_angle),
+                    )
+                });
+                (belt.shader.as_str(), rocks.collect())
+            })
+            .collect();
+        if let Some((star, host)) = trojan_host {
+            let rocks = trojans.rocks(star, host, orbit_angle);
+            let rocks =
+                rocks.map(|(trojan, position, rotation)| (position, trojan.scale, rotation));
+            swarms.push(("moon", rocks.collect()));
+        }
+        if let Some(probe) = &probe {
+            swarms.push(("moon", vec![(probe.position, PROBE_SIZE, Vector3::zero())]));
+        }
+        for (shader, rocks) in &swarms {
+            let (near, far): (Vec<_>, Vec<_>) = rocks
+                .iter()
+                .enumerate()
+                .map(|(index, &(position, scale, rotation))| {
+                    let pixels = scale * 0.5 * focal_length / position.distance_to(camera_position);
+                    let instance = Instance {
+                        model_matrix: create_model_matrix(position, scale, rotation),
+                        shader: None,
+                        params: None,
+                        seed: Some(index as u64),
+                    };
+                    (instance, pixels)
+                })
+                .partition(|(_, pixels)| *pixels >= 1.0);
+            let rock_command = DrawCommand {
+                mesh: rock,
+                model_matrix: Matrix::identity(),
+                shader: registry.resolve(shader).unwrap_or(&moon_shader),
+                params: &NO_PARAMS,
+                seed: 0,
+                state: RenderState {
+                    fill_mode: render_mode,
+                    ..RenderState::default()
+                },
+                scissor: None,
+                stencil: StencilState::default(),
+            };
+            let near: Vec<Instance> = near.into_iter().map(|(instance, _)| instance).collect();
+            let far: Vec<Instance> = far.into_iter().map(|(instance, _)| instance).collect();
+            commands.draw_instanced(rock_command, &near);
+            // Sin iluminar: un punto suelto no tiene una normal con sentido
+            commands.draw_instanced(
+                DrawCommand {
+                    mesh: speck,
+                    state: RenderState {
+                        fill_mode: RenderMode::Points,
+                        lighting: false,
+                        ..RenderState::default()
+                    },
+                    ..rock_command
+                },
+                &far,
+            );
+        }
+
+        // Colas de cometa y escombros: puntos translúcidos ya en espacio de
+        // mundo
+        let tail_meshes = tails.iter().map(|tail| &tail.mesh);
+        for mesh in tail_meshes.chain([&debris_mesh]) {
+            commands.draw(DrawCommand {
+                mesh: *mesh,
+                model_matrix: Matrix::identity(),
+                shader: &tail_shader,
+                params: &NO_PARAMS,
+                seed: 0,
+                state: RenderState {
+                    fill_mode: RenderMode::Points,
+                    blend_mode: BlendMode::Alpha,
+                    depth_write: false,
+                    ..RenderState::default()
+                },
+                scissor: None,
+                stencil: StencilState::default(),
+            });
+        }
+
+        // Anillos en el plano ecuatorial de cada cuerpo que los declara:
+        // siguen su inclinación pero no su giro, y se ven por ambas caras
+        commands.draw_instanced(
+            DrawCommand {
+                mesh: ring,
+                model_matrix: Matrix::identity(),
+                shader: &ring_shader,
+                params: &NO_PARAMS,
+                seed: 0,
+                state: RenderState {
+                    cull_mode: CullMode::None,
+                    blend_mode: BlendMode::Alpha,
+                    depth_write: false,
+                    fill_mode: render_mode,
+                    depth_bias: COPLANAR_DEPTH_BIAS,
+                    slope_depth_bias: COPLANAR_SLOPE_BIAS,
+                    ..RenderState::default()
+                },
+                scissor: None,
+                stencil: StencilState::default(),
+            },
+            &rings,
+        );
+
+        renderer.execute(&mut framebuffer, &commands);
+
+        // Órbitas (L): la elipse de cada cuerpo alrededor de la posición
+        // actual de su padre, tapada por lo que esté delante
         let uniforms = Uniforms {
-            model_matrix: gas_matrix,
+            model_matrix: Matrix::identity(),
+            view_matrix,
+            projection_matrix,
+            viewport_matrix,
+            camera_position,
+            lights: &lights,
+            occluders: &occluders,
+            params: &NO_PARAMS,
+            seed: 0,
+            time: 0.0,
         };
-        render_with_shader(&mut framebuffer, &uniforms, &vertex_array, gas_shader);
+        // Tapadas por lo que esté delante, sin escribir profundidad
+        let lines = RenderState {
+            depth_func: DepthFunc::LessEqual,
+            depth_write: false,
+            depth_bias: COPLANAR_DEPTH_BIAS,
+            ..RenderState::default()
+        };
+        if show_orbits {
+            for (index, placed) in placed.iter().enumerate() {
+                // Con gravedad las órbitas ya no son elipses fijas: se dibuja
+                // el rastro que fue dejando cada cuerpo
+                let path: Vec<Vector3> = match (&gravity, placed.body.orbit) {
+                    (Some(gravity), _) => gravity.trails[index].iter().copied().collect(),
+                    (None, Some(orbit)) => {
+                        let center = placed.position - orbit.offset(orbit_angle);
+                        orbit.path(128).iter().map(|&p| center + p).collect()
+                    }
+                    (None, None) => continue,
+                };
+                let color = if index == selected_body {
+                    Vector3::new(0.9, 0.8, 0.3)
+                } else {
+                    Vector3::new(0.25, 0.3, 0.45)
+                };
+                draw_polyline(&mut framebuffer, &uniforms, &path, color, &lines);
+            }
+        }
+
+        // Ruta que le queda a la sonda y el rastro que ya dejó
+        if let Some(probe) = &probe {
+            if show_orbits {
+                let path = Probe::path(&waypoints, 24);
+                draw_polyline(
+                    &mut framebuffer,
+                    &uniforms,
+                    &path,
+                    Vector3::new(0.3, 0.4, 0.3),
+                    &lines,
+                );
+            }
+            let trail: Vec<Vector3> = probe.trail.iter().copied().collect();
+            draw_polyline(
+                &mut framebuffer,
+                &uniforms,
+                &trail,
+                Vector3::new(0.5, 1.0, 0.6),
+                &lines,
+            );
+        }
 
+        // Puntos de Lagrange: un círculo en el plano de las órbitas y su nombre
+        if let Some((star, host)) = trojan_host {
+            let radius = 0.05 * star.distance_to(host);
+            let color = Vector3::new(0.4, 0.85, 0.6);
+            for (point, name) in lagrange_points(star, host).into_iter().zip(["L4", "L5"]) {
+                let circle: Vec<Vector3> = (0..=48)
+                    .map(|i| {
+                        let (sin, cos) = (i as f32 / 48.0 * 2.0 * PI).sin_cos();
+                        point + Vector3::new(cos, sin, 0.0) * radius
+                    })
+                    .collect();
+                draw_polyline(&mut framebuffer, &uniforms, &circle, color, &lines);
+                draw_label(
+                    &mut framebuffer,
+                    &uniforms,
+                    point,
+                    2.0 * radius,
+                    name,
+                    focal_length,
+                    color,
+                );
+            }
+        }
+
+        // Nombres (E) encima de todo, más tenues cuanto más lejos
+        if show_labels {
+            for (index, placed) in placed.iter().enumerate() {
+                let color = if index == selected_body {
+                    Vector3::new(0.9, 0.8, 0.3)
+                } else {
+                    Vector3::new(0.85, 0.9, 1.0)
+                };
+                draw_label(
+                    &mut framebuffer,
+                    &uniforms,
+                    placed.position,
+                    placed.body.scale,
+                    &placed.body.name,
+                    focal_length,
+                    color,
+                );
+            }
+        }
+
+        // Triángulos y fragmentos descartados por datos inválidos en este frame
+        let frame_stats = stats::take_frame_stats();
+        let mut overlay = clock.label();
+        if gravity.is_some() {
+            overlay += "  gravedad";
+        }
+        if let Some(target) = placed.get(selected_body) {
+            match camera_mode {
+                CameraMode::Body => overlay += &format!("  cámara: {}", target.body.name),
+                CameraMode::Follow => overlay += &format!("  siguiendo: {}", target.body.name),
+                CameraMode::System | CameraMode::Free => {}
+            }
+        }
+        if camera_mode == CameraMode::Free {
+            overlay += "  vuelo libre";
+        }
+        if let Some(probe) = &probe {
+            overlay += match (ride_probe, probe.finished(&waypoints)) {
+                (_, true) => "  sonda: llegó",
+                (true, false) => "  a bordo de la sonda",
+                (false, false) => "  sonda",
+            };
+        }
+        if show_stats {
+            overlay += &format!(
+                "\ndegenerados: {}  no finitos: {}  fragmentos NaN: {}",
+                frame_stats.degenerate_triangles,
+                frame_stats.non_finite_triangles,
+                frame_stats.non_finite_fragments,
+            );
+        }
+        framebuffer.set_overlay_text(Some(overlay));
         framebuffer.swap_buffers(&mut window, &thread);
         thread::sleep(Duration::from_millis(16));
     }
@@ -0,0 +1,175 @@
+#![allow(dead_code)]
+
+mod perlin;
+mod simplex;
+mod worley;
+
+pub use perlin::Perlin;
+pub use simplex::Simplex;
+pub use worley::Worley;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use raylib::prelude::*;
+
+// Ruido coherente en 2D y 3D con salida aproximada en [-1, 1]
+pub trait Noise {
+    fn noise2(&self, x: f32, y: f32) -> f32;
+    fn noise3(&self, x: f32, y: f32, z: f32) -> f32;
+}
+
+// Suma de octavas (fBm): cada octava multiplica la frecuencia por
+// `lacunarity` y la amplitud por `gain`. El resultado se normaliza a [-1, 1]
+#[derive(Clone, Copy, Debug)]
+pub struct Fbm {
+    pub octaves: u32,
+    pub lacunarity: f32,
+    pub gain: f32,
+}
+
+impl Fbm {
+    pub fn new(octaves: u32) -> Self {
+        Fbm {
+            octaves,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+
+    pub fn sample2(&self, noise: &impl Noise, x: f32, y: f32) -> f32 {
+        self.accumulate(|frequency| noise.noise2(x * frequency, y * frequency))
+    }
+
+    pub fn sample3(&self, noise: &impl Noise, x: f32, y: f32, z: f32) -> f32 {
+        self.accumulate(|frequency| noise.noise3(x * frequency, y * frequency, z * frequency))
+    }
+
+    fn accumulate(&self, octave: impl Fn(f32) -> f32) -> f32 {
+        let mut sum = 0.0;
+        let mut total_amplitude = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        for _ in 0..self.octaves {
+            sum += octave(frequency) * amplitude;
+            total_amplitude += amplitude;
+            amplitude *= self.gain;
+            frequency *= self.lacunarity;
+        }
+        if total_amplitude > 0.0 {
+            sum / total_amplitude
+        } else {
+            0.0
+        }
+    }
+}
+
+// Deformación del dominio: antes de muestrear se desplaza el punto con otro
+// ruido (ruido de ruido), lo que convierte franjas rectas en remolinos
+#[derive(Clone, Copy, Debug)]
+pub struct DomainWarp {
+    pub fbm: Fbm,
+    // Desplazamiento máximo, en las mismas unidades que el punto
+    pub strength: f32,
+}
+
+impl DomainWarp {
+    pub fn new(octaves: u32, strength: f32) -> Self {
+        DomainWarp {
+            fbm: Fbm::new(octaves),
+            strength,
+        }
+    }
+
+    pub fn warp2(&self, noise: &impl Noise, p: Vector2) -> Vector2 {
+        // Cada componente usa un desfase distinto para que no estén correlacionados
+        let offset = Vector2::new(
+            self.fbm.sample2(noise, p.x, p.y),
+            self.fbm.sample2(noise, p.x + 5.2, p.y + 1.3),
+        );
+        p + offset * self.strength
+    }
+
+    pub fn warp3(&self, noise: &impl Noise, p: Vector3) -> Vector3 {
+        let offset = Vector3::new(
+            self.fbm.sample3(noise, p.x, p.y, p.z),
+            self.fbm.sample3(noise, p.x + 5.2, p.y + 1.3, p.z + 2.8),
+            self.fbm.sample3(noise, p.x + 1.7, p.y + 9.2, p.z + 4.1),
+        );
+        p + offset * self.strength
+    }
+
+    // Dos pasadas encadenadas: el resultado se deforma otra vez, con
+    // remolinos más marcados
+    pub fn warp3_twice(&self, noise: &impl Noise, p: Vector3) -> Vector3 {
+        self.warp3(noise, self.warp3(noise, p))
+    }
+}
+
+// Desplazamiento del punto de muestreo a partir de la semilla de un cuerpo,
+// para que dos cuerpos con el mismo shader no salgan idénticos. La semilla 0
+// no desplaza nada.
+// Se llama por fragmento, así que usa un hash barato (splitmix64) en vez de
+// un generador.
+pub fn seed_offset(seed: u64) -> Vector3 {
+    if seed == 0 {
+        return Vector3::zero();
+    }
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        // 24 bits alcanzan para la precisión de un f32
+        (z >> 40) as f32 / (1u64 << 24) as f32 * 64.0
+    };
+    Vector3::new(next(), next(), next())
+}
+
+// Permutación de 0..=255 barajada con la semilla y duplicada, para no tener
+// que envolver los índices al encadenar hashes
+fn permutation_table(seed: u64) -> [u8; 512] {
+    let mut table: Vec<u8> = (0..=255).collect();
+    table.shuffle(&mut StdRng::seed_from_u64(seed));
+
+    let mut permutation = [0; 512];
+    for (i, slot) in permutation.iter_mut().enumerate() {
+        *slot = table[i & 255];
+    }
+    permutation
+}
+
+// Producto punto con uno de ocho gradientes (ejes y diagonales) elegido por el hash
+fn gradient2(hash: usize, x: f32, y: f32) -> f32 {
+    match hash & 7 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+// Producto punto con una de las doce aristas de un cubo, como en la
+// implementación de referencia de Perlin
+fn gradient3(hash: usize, x: f32, y: f32, z: f32) -> f32 {
+    match hash & 15 {
+        0 | 12 => x + y,
+        1 | 14 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x + z,
+        5 => -x + z,
+        6 => x - z,
+        7 => -x - z,
+        8 => y + z,
+        9 | 13 => -y + z,
+        10 => y - z,
+        _ => -y - z,
+    }
+}
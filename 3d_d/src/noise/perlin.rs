@@ -0,0 +1,99 @@
+use super::{Noise, gradient2, gradient3, permutation_table};
+
+// Ruido Perlin clásico con tabla de permutación barajada a partir de una semilla
+#[derive(Clone)]
+pub struct Perlin {
+    permutation: [u8; 512],
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        Perlin {
+            permutation: permutation_table(seed),
+        }
+    }
+
+    fn hash(&self, i: usize) -> usize {
+        self.permutation[i] as usize
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + t * (b - a)
+}
+
+impl Noise for Perlin {
+    fn noise2(&self, x: f32, y: f32) -> f32 {
+        let xi = x.floor() as i32 as usize & 255;
+        let yi = y.floor() as i32 as usize & 255;
+        let xf = x - x.floor();
+        let yf = y - y.floor();
+        let u = fade(xf);
+        let v = fade(yf);
+
+        let aa = self.hash(self.hash(xi) + yi);
+        let ab = self.hash(self.hash(xi) + yi + 1);
+        let ba = self.hash(self.hash(xi + 1) + yi);
+        let bb = self.hash(self.hash(xi + 1) + yi + 1);
+
+        let x1 = lerp(gradient2(aa, xf, yf), gradient2(ba, xf - 1.0, yf), u);
+        let x2 = lerp(
+            gradient2(ab, xf, yf - 1.0),
+            gradient2(bb, xf - 1.0, yf - 1.0),
+            u,
+        );
+        // Las diagonales llegan hasta ~1.41; se escala para quedar en [-1, 1]
+        lerp(x1, x2, v) * std::f32::consts::FRAC_1_SQRT_2
+    }
+
+    fn noise3(&self, x: f32, y: f32, z: f32) -> f32 {
+        let xi = x.floor() as i32 as usize & 255;
+        let yi = y.floor() as i32 as usize & 255;
+        let zi = z.floor() as i32 as usize & 255;
+        let xf = x - x.floor();
+        let yf = y - y.floor();
+        let zf = z - z.floor();
+        let u = fade(xf);
+        let v = fade(yf);
+        let w = fade(zf);
+
+        let a = self.hash(xi) + yi;
+        let aa = self.hash(a) + zi;
+        let ab = self.hash(a + 1) + zi;
+        let b = self.hash(xi + 1) + yi;
+        let ba = self.hash(b) + zi;
+        let bb = self.hash(b + 1) + zi;
+
+        let near = lerp(
+            lerp(
+                gradient3(self.hash(aa), xf, yf, zf),
+                gradient3(self.hash(ba), xf - 1.0, yf, zf),
+                u,
+            ),
+            lerp(
+                gradient3(self.hash(ab), xf, yf - 1.0, zf),
+                gradient3(self.hash(bb), xf - 1.0, yf - 1.0, zf),
+                u,
+            ),
+            v,
+        );
+        let far = lerp(
+            lerp(
+                gradient3(self.hash(aa + 1), xf, yf, zf - 1.0),
+                gradient3(self.hash(ba + 1), xf - 1.0, yf, zf - 1.0),
+                u,
+            ),
+            lerp(
+                gradient3(self.hash(ab + 1), xf, yf - 1.0, zf - 1.0),
+                gradient3(self.hash(bb + 1), xf - 1.0, yf - 1.0, zf - 1.0),
+                u,
+            ),
+            v,
+        );
+        lerp(near, far, w)
+    }
+}
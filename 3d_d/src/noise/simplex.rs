@@ -0,0 +1,120 @@
+use super::{Noise, gradient2, gradient3, permutation_table};
+
+// Ruido simplex (Gustavson): interpola sobre triángulos/tetraedros en vez de
+// una rejilla, así es más barato en 3D y no marca los ejes
+#[derive(Clone)]
+pub struct Simplex {
+    permutation: [u8; 512],
+}
+
+impl Simplex {
+    pub fn new(seed: u64) -> Self {
+        Simplex {
+            permutation: permutation_table(seed),
+        }
+    }
+
+    fn hash(&self, i: usize) -> usize {
+        self.permutation[i] as usize
+    }
+}
+
+const F2: f32 = 0.366_025_42; // (sqrt(3) - 1) / 2
+const G2: f32 = 0.211_324_87; // (3 - sqrt(3)) / 6
+const F3: f32 = 1.0 / 3.0;
+const G3: f32 = 1.0 / 6.0;
+
+// Aporte de una esquina: decae a cero a una distancia fija
+fn corner2(gradient: f32, x: f32, y: f32) -> f32 {
+    let t = 0.5 - x * x - y * y;
+    if t < 0.0 { 0.0 } else { t.powi(4) * gradient }
+}
+
+fn corner3(gradient: f32, x: f32, y: f32, z: f32) -> f32 {
+    let t = 0.6 - x * x - y * y - z * z;
+    if t < 0.0 { 0.0 } else { t.powi(4) * gradient }
+}
+
+impl Noise for Simplex {
+    fn noise2(&self, x: f32, y: f32) -> f32 {
+        // Celda del simplex que contiene el punto
+        let s = (x + y) * F2;
+        let i = (x + s).floor();
+        let j = (y + s).floor();
+        let t = (i + j) * G2;
+        let x0 = x - (i - t);
+        let y0 = y - (j - t);
+
+        // Triángulo inferior o superior de la celda
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+
+        let x1 = x0 - i1 as f32 + G2;
+        let y1 = y0 - j1 as f32 + G2;
+        let x2 = x0 - 1.0 + 2.0 * G2;
+        let y2 = y0 - 1.0 + 2.0 * G2;
+
+        let ii = i as i32 as usize & 255;
+        let jj = j as i32 as usize & 255;
+        let h0 = self.hash(ii + self.hash(jj));
+        let h1 = self.hash(ii + i1 + self.hash(jj + j1));
+        let h2 = self.hash(ii + 1 + self.hash(jj + 1));
+
+        let n = corner2(gradient2(h0, x0, y0), x0, y0)
+            + corner2(gradient2(h1, x1, y1), x1, y1)
+            + corner2(gradient2(h2, x2, y2), x2, y2);
+        // Factor que deja el resultado aproximadamente en [-1, 1]
+        70.0 * n
+    }
+
+    fn noise3(&self, x: f32, y: f32, z: f32) -> f32 {
+        let s = (x + y + z) * F3;
+        let i = (x + s).floor();
+        let j = (y + s).floor();
+        let k = (z + s).floor();
+        let t = (i + j + k) * G3;
+        let x0 = x - (i - t);
+        let y0 = y - (j - t);
+        let z0 = z - (k - t);
+
+        // Cuál de los seis tetraedros del cubo contiene el punto
+        let (i1, j1, k1, i2, j2, k2) = if x0 >= y0 {
+            if y0 >= z0 {
+                (1, 0, 0, 1, 1, 0)
+            } else if x0 >= z0 {
+                (1, 0, 0, 1, 0, 1)
+            } else {
+                (0, 0, 1, 1, 0, 1)
+            }
+        } else if y0 < z0 {
+            (0, 0, 1, 0, 1, 1)
+        } else if x0 < z0 {
+            (0, 1, 0, 0, 1, 1)
+        } else {
+            (0, 1, 0, 1, 1, 0)
+        };
+
+        let x1 = x0 - i1 as f32 + G3;
+        let y1 = y0 - j1 as f32 + G3;
+        let z1 = z0 - k1 as f32 + G3;
+        let x2 = x0 - i2 as f32 + 2.0 * G3;
+        let y2 = y0 - j2 as f32 + 2.0 * G3;
+        let z2 = z0 - k2 as f32 + 2.0 * G3;
+        let x3 = x0 - 1.0 + 3.0 * G3;
+        let y3 = y0 - 1.0 + 3.0 * G3;
+        let z3 = z0 - 1.0 + 3.0 * G3;
+
+        let ii = i as i32 as usize & 255;
+        let jj = j as i32 as usize & 255;
+        let kk = k as i32 as usize & 255;
+        let h0 = self.hash(ii + self.hash(jj + self.hash(kk)));
+        let h1 = self.hash(ii + i1 + self.hash(jj + j1 + self.hash(kk + k1)));
+        let h2 = self.hash(ii + i2 + self.hash(jj + j2 + self.hash(kk + k2)));
+        let h3 = self.hash(ii + 1 + self.hash(jj + 1 + self.hash(kk + 1)));
+
+        let n = corner3(gradient3(h0, x0, y0, z0), x0, y0, z0)
+            + corner3(gradient3(h1, x1, y1, z1), x1, y1, z1)
+            + corner3(gradient3(h2, x2, y2, z2), x2, y2, z2)
+            + corner3(gradient3(h3, x3, y3, z3), x3, y3, z3);
+        32.0 * n
+    }
+}
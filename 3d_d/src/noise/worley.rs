@@ -0,0 +1,101 @@
+use super::{Noise, permutation_table};
+
+// Distancias al punto característico más cercano (F1) y al segundo (F2)
+#[derive(Clone, Copy, Debug)]
+pub struct CellDistances {
+    pub f1: f32,
+    pub f2: f32,
+}
+
+impl CellDistances {
+    fn far() -> Self {
+        CellDistances {
+            f1: f32::MAX,
+            f2: f32::MAX,
+        }
+    }
+
+    fn insert(&mut self, distance: f32) {
+        if distance < self.f1 {
+            self.f2 = self.f1;
+            self.f1 = distance;
+        } else if distance < self.f2 {
+            self.f2 = distance;
+        }
+    }
+}
+
+// Ruido celular (Worley): un punto característico por celda de la rejilla,
+// ubicado con la tabla de permutación
+#[derive(Clone)]
+pub struct Worley {
+    permutation: [u8; 512],
+}
+
+impl Worley {
+    pub fn new(seed: u64) -> Self {
+        Worley {
+            permutation: permutation_table(seed),
+        }
+    }
+
+    fn hash(&self, i: usize) -> usize {
+        self.permutation[i] as usize
+    }
+
+    // Desplazamiento en [0, 1) del punto de la celda a partir de su hash
+    fn offset(&self, h: usize, axis: usize) -> f32 {
+        self.hash((h + axis) & 511) as f32 / 256.0
+    }
+
+    pub fn cells2(&self, x: f32, y: f32) -> CellDistances {
+        let cx = x.floor() as i32;
+        let cy = y.floor() as i32;
+        let mut distances = CellDistances::far();
+
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (i, j) = (cx + dx, cy + dy);
+                let h = self.hash(i as usize & 255) + (j as usize & 255);
+                let px = i as f32 + self.offset(h, 0);
+                let py = j as f32 + self.offset(h, 1);
+                distances.insert(((px - x).powi(2) + (py - y).powi(2)).sqrt());
+            }
+        }
+        distances
+    }
+
+    pub fn cells3(&self, x: f32, y: f32, z: f32) -> CellDistances {
+        let cx = x.floor() as i32;
+        let cy = y.floor() as i32;
+        let cz = z.floor() as i32;
+        let mut distances = CellDistances::far();
+
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let (i, j, k) = (cx + dx, cy + dy, cz + dz);
+                    let h = self.hash(self.hash(i as usize & 255) + (j as usize & 255))
+                        + (k as usize & 255);
+                    let px = i as f32 + self.offset(h, 0);
+                    let py = j as f32 + self.offset(h, 1);
+                    let pz = k as f32 + self.offset(h, 2);
+                    distances
+                        .insert(((px - x).powi(2) + (py - y).powi(2) + (pz - z).powi(2)).sqrt());
+                }
+            }
+        }
+        distances
+    }
+}
+
+// Como `Noise` devuelve F1 llevado a [-1, 1], para poder sumarlo en octavas
+impl Noise for Worley {
+    fn noise2(&self, x: f32, y: f32) -> f32 {
+        self.cells2(x, y).f1.min(1.0) * 2.0 - 1.0
+    }
+
+    fn noise3(&self, x: f32, y: f32, z: f32) -> f32 {
+        self.cells3(x, y, z).f1.min(1.0) * 2.0 - 1.0
+    }
+}
@@ -1,10 +1,14 @@
 use crate::vertex::Vertex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use raylib::math::{Vector2, Vector3};
-use tobj;
+use std::f32::consts::PI;
 
 pub struct Obj {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
+    // Radio de la esfera que contiene el modelo, centrada en el origen local
+    pub bounding_radius: f32,
 }
 
 impl Obj {
@@ -17,12 +21,14 @@ impl Obj {
         for model in models {
             let mesh = &model.mesh;
             let num_vertices = mesh.positions.len() / 3;
+            // Los índices de cada modelo son locales a sus propios vértices
+            let index_offset = vertices.len() as u32;
 
             for i in 0..num_vertices {
                 let x = mesh.positions[i * 3];
                 let y = mesh.positions[i * 3 + 1];
                 let z = mesh.positions[i * 3 + 2];
-                let position = Vector3::new(x, -y, z);
+                let position = Vector3::new(x, y, z);
 
                 let normal = if !mesh.normals.is_empty() {
                     let nx = mesh.normals[i * 3];
@@ -43,17 +49,129 @@ impl Obj {
 
                 vertices.push(Vertex::new(position, normal, tex_coords));
             }
-            indices.extend_from_slice(&mesh.indices);
+            indices.extend(mesh.indices.iter().map(|&index| index + index_offset));
         }
 
-        Ok(Obj { vertices, indices })
+        let bounding_radius = vertices
+            .iter()
+            .map(|v| v.position.length())
+            .fold(0.0, f32::max);
+
+        Ok(Obj {
+            vertices,
+            indices,
+            bounding_radius,
+        })
+    }
+
+    // Anillo plano en el plano XZ con normal +Y. En las coordenadas de
+    // textura, u va de 0 (borde interior) a 1 (exterior) y v da la vuelta.
+    pub fn ring(inner_radius: f32, outer_radius: f32, segments: u32) -> Self {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let normal = Vector3::new(0.0, 1.0, 0.0);
+
+        for i in 0..=segments {
+            let v = i as f32 / segments as f32;
+            let (sin, cos) = (v * 2.0 * PI).sin_cos();
+            for (u, radius) in [(0.0, inner_radius), (1.0, outer_radius)] {
+                let position = Vector3::new(cos * radius, 0.0, sin * radius);
+                vertices.push(Vertex::new(position, normal, Vector2::new(u, v)));
+            }
+        }
+
+        for i in 0..segments {
+            let inner = i * 2;
+            let outer = inner + 1;
+            let next_inner = inner + 2;
+            let next_outer = inner + 3;
+            indices.extend([inner, outer, next_inner, next_inner, outer, next_outer]);
+        }
+
+        Obj {
+            vertices,
+            indices,
+            bounding_radius: outer_radius,
+        }
+    }
+
+    // Roca irregular y de pocos polígonos: un icosaedro con cada vértice a
+    // una distancia al azar del centro. Radio cercano a 0.5, como la esfera.
+    pub fn rock(seed: u64, roughness: f32) -> Self {
+        let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+        let corners = [
+            (-1.0, t, 0.0),
+            (1.0, t, 0.0),
+            (-1.0, -t, 0.0),
+            (1.0, -t, 0.0),
+            (0.0, -1.0, t),
+            (0.0, 1.0, t),
+            (0.0, -1.0, -t),
+            (0.0, 1.0, -t),
+            (t, 0.0, -1.0),
+            (t, 0.0, 1.0),
+            (-t, 0.0, -1.0),
+            (-t, 0.0, 1.0),
+        ];
+        let indices: Vec<u32> = vec![
+            0, 11, 5, 0, 5, 1, 0, 1, 7, 0, 7, 10, 0, 10, 11, 1, 5, 9, 5, 11, 4, 11, 10, 2, 10, 7,
+            6, 7, 1, 8, 3, 9, 4, 3, 4, 2, 3, 2, 6, 3, 6, 8, 3, 8, 9, 4, 9, 5, 2, 4, 11, 6, 2, 10,
+            8, 6, 7, 9, 8, 1,
+        ];
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let positions: Vec<Vector3> = corners
+            .iter()
+            .map(|&(x, y, z)| {
+                let radius = 0.5 * (1.0 - rng.gen_range(0.0..=roughness));
+                Vector3::new(x, y, z).normalized() * radius
+            })
+            .collect();
+
+        // Normal de cada vértice: promedio de las caras que lo tocan, para
+        // que la luz marque las irregularidades
+        let mut normals = vec![Vector3::zero(); positions.len()];
+        for face in indices.chunks(3) {
+            let [a, b, c] = [face[0], face[1], face[2]].map(|i| positions[i as usize]);
+            let normal = (b - a).cross(c - a);
+            for &i in face {
+                normals[i as usize] += normal;
+            }
+        }
+
+        let vertices = positions
+            .iter()
+            .zip(&normals)
+            .map(|(&position, &normal)| Vertex::new(position, normal.normalized(), Vector2::zero()))
+            .collect();
+        Obj {
+            vertices,
+            indices,
+            bounding_radius: 0.5,
+        }
+    }
+
+    // Nube de vértices sueltos para `RenderMode::Points`
+    pub fn points(vertices: Vec<Vertex>) -> Self {
+        let bounding_radius = vertices
+            .iter()
+            .map(|vertex| vertex.position.length())
+            .fold(0.0, f32::max);
+        Obj {
+            vertices,
+            indices: Vec::new(),
+            bounding_radius,
+        }
     }
 
-    pub fn get_vertex_array(&self) -> Vec<Vertex> {
-        let mut vertex_array = Vec::new();
-        for &index in &self.indices {
-            vertex_array.push(self.vertices[index as usize].clone());
+    // Un solo vértice, para dibujar con `RenderMode::Points` lo que está tan
+    // lejos que ocupa menos de un píxel
+    pub fn point() -> Self {
+        let position = Vector3::new(0.0, 0.5, 0.0);
+        Obj {
+            vertices: vec![Vertex::new(position, Vector3::up(), Vector2::zero())],
+            indices: Vec::new(),
+            bounding_radius: 0.5,
         }
-        vertex_array
     }
 }
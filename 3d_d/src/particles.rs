@@ -0,0 +1,120 @@
+use crate::obj::Obj;
+use crate::vertex::Vertex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use raylib::prelude::*;
+
+pub struct Particle {
+    pub position: Vector3,
+    pub velocity: Vector3,
+    pub age: f32,
+    pub lifetime: f32,
+}
+
+// Partículas sueltas en espacio de mundo que se mueven en línea recta hasta
+// que se les acaba la vida
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    rng: StdRng,
+    // Fracción de partícula que quedó sin emitir el frame anterior
+    pending: f32,
+}
+
+impl ParticleSystem {
+    pub fn new(seed: u64) -> Self {
+        ParticleSystem {
+            particles: Vec::new(),
+            rng: StdRng::seed_from_u64(seed),
+            pending: 0.0,
+        }
+    }
+
+    // Emite `rate` * `dt` partículas desde `origin`; cada una sale con
+    // `velocity` más un desvío al azar de hasta `spread` por eje
+    pub fn emit(
+        &mut self,
+        rate: f32,
+        dt: f32,
+        origin: Vector3,
+        velocity: Vector3,
+        spread: f32,
+        lifetime: f32,
+    ) {
+        self.pending += rate * dt;
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+            let jitter = Vector3::new(
+                self.rng.gen_range(-1.0..=1.0),
+                self.rng.gen_range(-1.0..=1.0),
+                self.rng.gen_range(-1.0..=1.0),
+            ) * spread;
+            self.particles.push(Particle {
+                position: origin,
+                velocity: velocity + jitter,
+                age: 0.0,
+                lifetime: lifetime * self.rng.gen_range(0.5..=1.0),
+            });
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for particle in &mut self.particles {
+            particle.position += particle.velocity * dt;
+            particle.age += dt;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+    }
+
+    // Un vértice por partícula, para dibujar con `RenderMode::Points`. El
+    // alfa va en la coordenada u y baja a cero al final de la vida.
+    pub fn mesh(&self, color: Vector3) -> Obj {
+        let vertices = self
+            .particles
+            .iter()
+            .map(|particle| {
+                let alpha = 1.0 - particle.age / particle.lifetime;
+                Vertex {
+                    tex_coords: Vector2::new(alpha, 0.0),
+                    ..Vertex::new_with_color(particle.position, color)
+                }
+            })
+            .collect();
+        Obj::points(vertices)
+    }
+}
+
+// Cola de cometa: gas y polvo que se alejan del sol. Cerca del perihelio el
+// sol calienta más el núcleo y la cola se vuelve más densa y más larga.
+pub struct CometTail {
+    particles: ParticleSystem,
+}
+
+impl CometTail {
+    pub fn new(seed: u64) -> Self {
+        CometTail {
+            particles: ParticleSystem::new(seed),
+        }
+    }
+
+    pub fn update(&mut self, nucleus: Vector3, sun: Vector3, dt: f32) {
+        let away = nucleus - sun;
+        let distance = away.length().max(1.0);
+        // 1 a unas 250 unidades del sol, y cae con el cuadrado de la distancia
+        let activity = (250.0 / distance).powi(2).min(4.0);
+
+        self.particles.update(dt);
+        self.particles.emit(
+            300.0 * activity,
+            dt,
+            nucleus,
+            away / distance * (30.0 + 50.0 * activity),
+            6.0,
+            1.5 + activity,
+        );
+    }
+
+    pub fn mesh(&self) -> Obj {
+        self.particles.mesh(Vector3::new(0.7, 0.85, 1.0))
+    }
+}
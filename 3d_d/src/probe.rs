@@ -0,0 +1,148 @@
+use crate::body::{CelestialBody, PlacedBody};
+use raylib::prelude::*;
+use std::collections::VecDeque;
+
+// A qué altura sobre cada cuerpo pasa la sonda, en radios del cuerpo
+const FLYBY_RADII: f32 = 3.0;
+const TRAIL_LENGTH: usize = 600;
+
+// El guion de un vuelo: los cuerpos a visitar, en orden, y la velocidad en
+// unidades de mundo por segundo
+#[derive(Clone, Debug)]
+pub struct ProbeSettings {
+    pub route: Vec<String>,
+    pub speed: f32,
+}
+
+impl Default for ProbeSettings {
+    fn default() -> Self {
+        ProbeSettings {
+            route: Vec::new(),
+            speed: 90.0,
+        }
+    }
+}
+
+impl ProbeSettings {
+    // Sin guion: todos los planetas de adentro hacia afuera
+    pub fn grand_tour(bodies: &[CelestialBody]) -> Self {
+        let mut planets: Vec<&CelestialBody> = bodies
+            .iter()
+            .filter(|body| body.light.is_none() && body.orbit.is_some())
+            .collect();
+        planets.sort_by(|a, b| {
+            let distance = |body: &CelestialBody| body.orbit.map_or(0.0, |o| o.semi_major_axis);
+            distance(a).total_cmp(&distance(b))
+        });
+        ProbeSettings {
+            route: planets.iter().map(|body| body.name.clone()).collect(),
+            ..ProbeSettings::default()
+        }
+    }
+}
+
+// Una sonda que recorre su ruta por una curva suave (Catmull-Rom) entre los
+// puntos de paso. Cada punto se ancla al cuerpo en cada frame, así la sonda
+// llega a todos aunque los planetas se muevan.
+pub struct Probe {
+    pub settings: ProbeSettings,
+    // Tramo actual y fracción recorrida de él
+    progress: f32,
+    pub position: Vector3,
+    // Dirección de avance, normalizada
+    pub heading: Vector3,
+    pub trail: VecDeque<Vector3>,
+}
+
+impl Probe {
+    pub fn new(settings: ProbeSettings) -> Self {
+        Probe {
+            settings,
+            progress: 0.0,
+            position: Vector3::zero(),
+            heading: Vector3::new(1.0, 0.0, 0.0),
+            trail: VecDeque::new(),
+        }
+    }
+
+    // Puntos de paso de este frame, por encima del polo de cada cuerpo; los
+    // nombres que ya no existen se saltan
+    pub fn waypoints(&self, placed: &[PlacedBody]) -> Vec<Vector3> {
+        self.settings
+            .route
+            .iter()
+            .filter_map(|name| placed.iter().find(|placed| placed.body.name == *name))
+            .map(|placed| placed.position + Vector3::new(0.0, 0.0, placed.radius() * FLYBY_RADII))
+            .collect()
+    }
+
+    pub fn finished(&self, waypoints: &[Vector3]) -> bool {
+        self.progress >= waypoints.len().saturating_sub(1) as f32
+    }
+
+    // Avanza `seconds` a velocidad constante sobre la curva
+    pub fn advance(&mut self, waypoints: &[Vector3], seconds: f32) {
+        let Some(&first) = waypoints.first() else {
+            return;
+        };
+        let segments = waypoints.len() - 1;
+        self.progress = self.progress.min(segments as f32);
+        if segments == 0 {
+            self.position = first;
+            return;
+        }
+
+        // La fracción del tramo avanza en proporción a su largo, así la
+        // sonda no acelera en los tramos largos
+        let segment = (self.progress as usize).min(segments - 1);
+        let length = waypoints[segment]
+            .distance_to(waypoints[segment + 1])
+            .max(1.0);
+        self.progress =
+            (self.progress + self.settings.speed * seconds / length).min(segments as f32);
+
+        let segment = (self.progress as usize).min(segments - 1);
+        let t = self.progress - segment as f32;
+        let point = |i: isize| waypoints[i.clamp(0, segments as isize) as usize];
+        let i = segment as isize;
+        let (p0, p1, p2, p3) = (point(i - 1), point(i), point(i + 1), point(i + 2));
+        let position = catmull_rom(p0, p1, p2, p3, t);
+        let step = position - self.position;
+        if step.dot(step) > 1e-6 {
+            self.heading = step.normalized();
+        }
+        self.position = position;
+
+        self.trail.push_back(position);
+        if self.trail.len() > TRAIL_LENGTH {
+            self.trail.pop_front();
+        }
+    }
+
+    // La ruta completa de este frame, para dibujarla
+    pub fn path(waypoints: &[Vector3], samples_per_segment: usize) -> Vec<Vector3> {
+        let segments = waypoints.len().saturating_sub(1);
+        let point = |i: isize| waypoints[i.clamp(0, segments as isize) as usize];
+        (0..segments)
+            .flat_map(|segment| {
+                let i = segment as isize;
+                (0..samples_per_segment).map(move |sample| {
+                    let t = sample as f32 / samples_per_segment as f32;
+                    catmull_rom(point(i - 1), point(i), point(i + 1), point(i + 2), t)
+                })
+            })
+            .chain(waypoints.last().copied())
+            .collect()
+    }
+}
+
+// Curva que pasa por `p1` en t = 0 y por `p2` en t = 1, con tangentes que
+// apuntan de `p0` a `p2` y de `p1` a `p3`
+fn catmull_rom(p0: Vector3, p1: Vector3, p2: Vector3, p3: Vector3, t: f32) -> Vector3 {
+    let (t2, t3) = (t * t, t * t * t);
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
@@ -0,0 +1,60 @@
+use crate::framebuffer::DepthFunc;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RenderMode {
+    Filled,
+    Wireframe,
+    Points,
+    // Relleno normal con las aristas de cada triángulo encima (depuración)
+    FilledWireframe,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CullMode {
+    None,
+    Back,
+    Front,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BlendMode {
+    Opaque,
+    // Source-over con el alfa que devuelve el shader
+    Alpha,
+}
+
+// Estado del pipeline para un draw; cada opción nueva se agrega aquí en vez
+// de como otro parámetro de `render_with_shader`
+#[derive(Clone, Copy, Debug)]
+pub struct RenderState {
+    pub cull_mode: CullMode,
+    pub blend_mode: BlendMode,
+    pub depth_func: DepthFunc,
+    pub depth_write: bool,
+    pub fill_mode: RenderMode,
+    // Se suma a la profundidad de cada fragmento (negativo = más cerca):
+    // `depth_bias` + `slope_depth_bias` * pendiente máxima del triángulo
+    pub depth_bias: f32,
+    pub slope_depth_bias: f32,
+    // Multiplica el color del shader por el difuso de la estrella; se apaga
+    // para lo que emite luz propia
+    pub lighting: bool,
+    // Iluminación en bandas y contorno oscuro en la silueta
+    pub toon: bool,
+}
+
+impl Default for RenderState {
+    fn default() -> Self {
+        RenderState {
+            cull_mode: CullMode::Back,
+            blend_mode: BlendMode::Opaque,
+            depth_func: DepthFunc::Less,
+            depth_write: true,
+            fill_mode: RenderMode::Filled,
+            depth_bias: 0.0,
+            slope_depth_bias: 0.0,
+            lighting: true,
+            toon: false,
+        }
+    }
+}
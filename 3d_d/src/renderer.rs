@@ -0,0 +1,269 @@
+use crate::clip::sphere_in_frustum;
+use crate::framebuffer::{DepthFunc, Framebuffer, StencilOp, StencilState};
+use crate::lighting::{Light, Occluder};
+use crate::obj::Obj;
+use crate::render_state::{BlendMode, RenderMode, RenderState};
+use crate::shader_params::ShaderParams;
+use crate::shaders::{DepthOnlyShader, FragmentShader};
+use crate::triangle::ScreenRect;
+use crate::{Uniforms, render_with_shader};
+use raylib::prelude::*;
+
+// Índice de un mesh registrado en el `Renderer`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MeshHandle(usize);
+
+#[derive(Clone, Copy)]
+pub struct DrawCommand<'a> {
+    pub mesh: MeshHandle,
+    pub model_matrix: Matrix,
+    pub shader: &'a dyn FragmentShader,
+    pub params: &'a ShaderParams,
+    // Desplaza el muestreo de ruido del shader (ver `noise::seed_offset`)
+    pub seed: u64,
+    pub state: RenderState,
+    pub scissor: Option<ScreenRect>,
+    pub stencil: StencilState,
+}
+
+// Una copia del mismo mesh con su propia matriz de modelo; sin shader o
+// parámetros propios usa los del comando
+pub struct Instance<'a> {
+    pub model_matrix: Matrix,
+    pub shader: Option<&'a dyn FragmentShader>,
+    pub params: Option<&'a ShaderParams>,
+    pub seed: Option<u64>,
+}
+
+// Draws grabados durante el frame junto con la cámara con la que se ejecutan
+pub struct CommandBuffer<'a> {
+    view_matrix: Matrix,
+    projection_matrix: Matrix,
+    viewport_matrix: Matrix,
+    lights: &'a [Light],
+    occluders: &'a [Occluder],
+    time: f32,
+    commands: Vec<DrawCommand<'a>>,
+}
+
+impl<'a> CommandBuffer<'a> {
+    pub fn new(
+        view_matrix: Matrix,
+        projection_matrix: Matrix,
+        viewport_matrix: Matrix,
+        lights: &'a [Light],
+        occluders: &'a [Occluder],
+        time: f32,
+    ) -> Self {
+        CommandBuffer {
+            view_matrix,
+            projection_matrix,
+            viewport_matrix,
+            lights,
+            occluders,
+            time,
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn draw(&mut self, command: DrawCommand<'a>) {
+        self.commands.push(command);
+    }
+
+    pub fn draw_instanced(&mut self, command: DrawCommand<'a>, instances: &[Instance<'a>]) {
+        for instance in instances {
+            self.draw(DrawCommand {
+                model_matrix: instance.model_matrix,
+                shader: instance.shader.unwrap_or(command.shader),
+                params: instance.params.unwrap_or(command.params),
+                seed: instance.seed.unwrap_or(command.seed),
+                ..command
+            });
+        }
+    }
+
+    fn uniforms<'p>(&self, command: &DrawCommand<'p>) -> Uniforms<'p>
+    where
+        'a: 'p,
+    {
+        Uniforms {
+            model_matrix: command.model_matrix,
+            view_matrix: self.view_matrix,
+            projection_matrix: self.projection_matrix,
+            viewport_matrix: self.viewport_matrix,
+            camera_position: camera_position(&self.view_matrix),
+            lights: self.lights,
+            occluders: self.occluders,
+            params: command.params,
+            seed: command.seed,
+            time: self.time,
+        }
+    }
+
+    // Profundidad del origen del modelo en espacio de vista (más negativo = más lejos)
+    fn view_depth(&self, command: &DrawCommand) -> f32 {
+        let model = &command.model_matrix;
+        let view = &self.view_matrix;
+        view.m2 * model.m12 + view.m6 * model.m13 + view.m10 * model.m14 + view.m14
+    }
+}
+
+// La vista es [R | t] con R ortonormal, así que la cámara está en -Rᵀt
+fn camera_position(view: &Matrix) -> Vector3 {
+    let right = Vector3::new(view.m0, view.m4, view.m8);
+    let up = Vector3::new(view.m1, view.m5, view.m9);
+    let back = Vector3::new(view.m2, view.m6, view.m10);
+    -(right * view.m12 + up * view.m13 + back * view.m14)
+}
+
+pub struct Renderer {
+    meshes: Vec<Obj>,
+    // Lugares de meshes quitados, que se reusan al agregar otros
+    free: Vec<usize>,
+    pub tiled: bool,
+    // Los opacos rellenos se rasterizan primero solo a profundidad y luego se
+    // sombrean con prueba EQUAL, así el shader corre solo en lo visible
+    pub depth_prepass: bool,
+    // Sombreado caricatura para todos los draws del frame
+    pub toon: bool,
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Renderer {
+            meshes: Vec::new(),
+            free: Vec::new(),
+            tiled: false,
+            depth_prepass: false,
+            toon: false,
+        }
+    }
+
+    pub fn add_mesh(&mut self, mesh: Obj) -> MeshHandle {
+        if let Some(index) = self.free.pop() {
+            self.meshes[index] = mesh;
+            return MeshHandle(index);
+        }
+        self.meshes.push(mesh);
+        MeshHandle(self.meshes.len() - 1)
+    }
+
+    // El handle no se debe volver a usar: su lugar queda para otro mesh
+    pub fn remove_mesh(&mut self, handle: MeshHandle) {
+        self.meshes[handle.0] = Obj::points(Vec::new());
+        self.free.push(handle.0);
+    }
+
+    // Para geometría que se rehace cada frame, como las partículas
+    pub fn replace_mesh(&mut self, handle: MeshHandle, mesh: Obj) {
+        self.meshes[handle.0] = mesh;
+    }
+
+    // Descarta lo que queda fuera del frustum, dibuja los opacos en orden de
+    // grabación y después los translúcidos de atrás hacia adelante
+    pub fn execute(&self, framebuffer: &mut Framebuffer, commands: &CommandBuffer) {
+        let view_projection = commands.view_matrix * commands.projection_matrix;
+        let visible: Vec<&DrawCommand> = commands
+            .commands
+            .iter()
+            .filter(|command| self.in_frustum(&view_projection, command))
+            .collect();
+
+        let (opaque, mut transparent): (Vec<&DrawCommand>, Vec<&DrawCommand>) = visible
+            .into_iter()
+            .partition(|command| command.state.blend_mode == BlendMode::Opaque);
+        transparent.sort_by(|a, b| commands.view_depth(a).total_cmp(&commands.view_depth(b)));
+
+        let prepassed = |command: &DrawCommand| {
+            self.depth_prepass && command.state.fill_mode == RenderMode::Filled
+        };
+
+        framebuffer.set_color_write(false);
+        for command in opaque.iter().filter(|command| prepassed(command)) {
+            let state = RenderState {
+                depth_write: true,
+                ..command.state
+            };
+            let stencil = StencilState {
+                pass_op: StencilOp::Keep,
+                ..command.stencil
+            };
+            self.run(
+                framebuffer,
+                commands,
+                command,
+                &DepthOnlyShader,
+                &state,
+                stencil,
+            );
+        }
+        framebuffer.set_color_write(true);
+
+        for command in &opaque {
+            let state = if prepassed(command) {
+                RenderState {
+                    depth_func: DepthFunc::Equal,
+                    depth_write: false,
+                    ..command.state
+                }
+            } else {
+                command.state
+            };
+            self.run(
+                framebuffer,
+                commands,
+                command,
+                command.shader,
+                &state,
+                command.stencil,
+            );
+        }
+
+        for command in &transparent {
+            self.run(
+                framebuffer,
+                commands,
+                command,
+                command.shader,
+                &command.state,
+                command.stencil,
+            );
+        }
+    }
+
+    fn in_frustum(&self, view_projection: &Matrix, command: &DrawCommand) -> bool {
+        let m = &command.model_matrix;
+        let center = Vector3::new(m.m12, m.m13, m.m14);
+        let scale = Vector3::new(m.m0, m.m1, m.m2)
+            .length()
+            .max(Vector3::new(m.m4, m.m5, m.m6).length())
+            .max(Vector3::new(m.m8, m.m9, m.m10).length());
+        let radius = self.meshes[command.mesh.0].bounding_radius * scale;
+        sphere_in_frustum(view_projection, center, radius)
+    }
+
+    fn run(
+        &self,
+        framebuffer: &mut Framebuffer,
+        commands: &CommandBuffer,
+        command: &DrawCommand,
+        shader: &dyn FragmentShader,
+        state: &RenderState,
+        stencil: StencilState,
+    ) {
+        framebuffer.set_scissor(command.scissor);
+        framebuffer.set_stencil_state(stencil);
+        let state = RenderState {
+            toon: state.toon || self.toon,
+            ..*state
+        };
+        render_with_shader(
+            framebuffer,
+            &commands.uniforms(command),
+            &self.meshes[command.mesh.0],
+            shader,
+            &state,
+            self.tiled,
+        );
+    }
+}
@@ -0,0 +1,715 @@
+// Carga de sistemas desde archivos de texto con un subconjunto de TOML, para
+// compartir y ajustar escenas sin recompilar:
+//
+//     [[body]]
+//     name = "Tierra"
+//     shader = "earth"
+//     scale = 30
+//     seed = 5
+//     semi_major_axis = 430
+//     orbit_speed = 0.5
+//
+//     [body.params]
+//     twilight_color = [1.0, 0.55, 0.3]
+//
+//     [[body.shell]]
+//     shader = "earth_atmosphere"
+//     scale = 1.08
+//
+//     [[body.moon]]
+//     name = "Luna"
+//     ...
+//
+//     [[belt]]
+//     shader = "moon"
+//     count = 400
+//     inner_radius = 575
+//     outer_radius = 615
+//
+//     [probe]
+//     route = "Tierra, Marte, Júpiter"
+//     speed = 90
+//
+//     [[event]]
+//     time = 6.28
+//     action = "orbit_radius"
+//     body = "Marte"
+//     value = 520
+//
+// Cada `.moon` agrega un nivel: `[[body.moon]]` orbita al último `[[body]]`,
+// `[[body.moon.moon]]` a la última luna, etc. `shell` y `params` se aplican al
+// último cuerpo del mismo nivel. Dentro de un texto, `\"` es una comilla y
+// `\\` una barra.
+//
+// Una partida guardada es una escena más con una tabla `[state]` al final:
+// tiempo, cámara (y su modo), semilla y, con la gravedad activa, posiciones y
+// velocidades.
+
+use crate::belt::{AsteroidBelt, BeltSettings};
+use crate::body::{CelestialBody, Orbit, Shell};
+use crate::camera::{Camera, CameraMode};
+use crate::events::{EventAction, EventSettings};
+use crate::probe::ProbeSettings;
+use raylib::prelude::*;
+use std::f32::consts::PI;
+use std::fs;
+
+// El Sistema Solar real va dentro del binario, así funciona sin assets
+const SOLAR_SYSTEM: &str = include_str!("../assets/scenes/sistema_solar.toml");
+
+#[derive(Debug)]
+enum Value {
+    Number(f32),
+    Text(String),
+    Numbers(Vec<f32>),
+    Bool(bool),
+}
+
+impl Value {
+    fn number(&self) -> Result<f32, String> {
+        match self {
+            Value::Number(value) => Ok(*value),
+            _ => Err("se esperaba un número".to_string()),
+        }
+    }
+
+    fn flag(&self) -> Result<bool, String> {
+        match self {
+            Value::Bool(flag) => Ok(*flag),
+            _ => Err("se esperaba true o false".to_string()),
+        }
+    }
+
+    fn color(&self) -> Result<Vector3, String> {
+        match self {
+            Value::Numbers(rgb) if rgb.len() == 3 => Ok(Vector3::new(rgb[0], rgb[1], rgb[2])),
+            _ => Err("se esperaba un color [r, g, b]".to_string()),
+        }
+    }
+
+    // [x, y, z, x, y, z, ...]
+    fn vectors(&self) -> Result<Vec<Vector3>, String> {
+        match self {
+            Value::Numbers(numbers) if numbers.len() % 3 == 0 => Ok(numbers
+                .chunks_exact(3)
+                .map(|xyz| Vector3::new(xyz[0], xyz[1], xyz[2]))
+                .collect()),
+            _ => Err("se esperaba una lista [x, y, z, ...]".to_string()),
+        }
+    }
+
+    fn text(&self) -> Result<String, String> {
+        match self {
+            Value::Text(text) => Ok(text.clone()),
+            _ => Err("se esperaba un texto entre comillas".to_string()),
+        }
+    }
+
+    // La cámara completa; las partidas viejas guardaban solo yaw, pitch y
+    // distancia alrededor del sol
+    fn camera(&self) -> Result<Camera, String> {
+        match self {
+            Value::Numbers(numbers) if numbers.len() == 3 => {
+                let mut camera = Camera::new(Vector3::zero(), Vector3::zero(), PI / 4.0);
+                camera.set_orbit(numbers[0], numbers[1], numbers[2]);
+                Ok(camera)
+            }
+            Value::Numbers(numbers) => {
+                Camera::from_numbers(numbers).ok_or("se esperaba una cámara".to_string())
+            }
+            _ => Err("se esperaba una cámara".to_string()),
+        }
+    }
+
+    // Las semillas grandes van entre comillas porque como número pierden
+    // precisión al leerse como f32; las chicas escritas a mano, sin ellas
+    fn seed(&self) -> Result<u64, String> {
+        match self {
+            Value::Number(value) if *value >= 0.0 => Ok(*value as u64),
+            Value::Text(seed) => seed
+                .parse()
+                .map_err(|_| format!("semilla inválida '{seed}'")),
+            _ => Err("se esperaba una semilla".to_string()),
+        }
+    }
+}
+
+// Qué tabla reciben las líneas `clave = valor` que siguen
+#[derive(Clone, Copy)]
+enum Section {
+    None,
+    Body(usize),
+    Shell(usize),
+    Params(usize),
+    Belt,
+    Event,
+    Probe,
+    State,
+}
+
+pub struct Scene {
+    pub bodies: Vec<CelestialBody>,
+    pub belts: Vec<AsteroidBelt>,
+    // Ruta guionada de la sonda, si la escena trae una
+    pub probe: Option<ProbeSettings>,
+    // Cambios programados en el tiempo de simulación
+    pub events: Vec<EventSettings>,
+    // Solo en las partidas guardadas
+    pub snapshot: Option<Snapshot>,
+}
+
+// Lo que no está en la escena y hace falta para seguir donde se dejó
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub time: f32,
+    pub time_scale: f32,
+    pub paused: bool,
+    pub camera: Option<Camera>,
+    pub camera_mode: CameraMode,
+    // Desplazamiento de la cámara de persecución (ver `Chase`)
+    pub chase: Option<Vector3>,
+    pub seed: Option<u64>,
+    pub selected: usize,
+    // Posiciones y velocidades del modo de gravedad, en preorden
+    pub gravity: Option<(Vec<Vector3>, Vec<Vector3>)>,
+}
+
+impl Snapshot {
+    pub fn new() -> Self {
+        Snapshot {
+            time: 0.0,
+            time_scale: 1.0,
+            paused: false,
+            camera: None,
+            camera_mode: CameraMode::System,
+            chase: None,
+            seed: None,
+            selected: 0,
+            gravity: None,
+        }
+    }
+}
+
+pub fn load(path: &str) -> Result<Scene, String> {
+    let source = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    parse(&source).map_err(|e| format!("{path}: {e}"))
+}
+
+pub fn solar_system() -> Scene {
+    parse(SOLAR_SYSTEM).expect("❌ El preset del Sistema Solar no es válido")
+}
+
+pub fn parse(source: &str) -> Result<Scene, String> {
+    let mut bodies = Vec::new();
+    let mut belts = Vec::new();
+    let mut probe = None;
+    let mut events = Vec::new();
+    let mut snapshot = None;
+    let mut section = Section::None;
+
+    for (index, line) in source.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message: String| format!("línea {}: {message}", index + 1);
+
+        if line == "[[belt]]" {
+            belts.push(BeltSettings::default());
+            section = Section::Belt;
+        } else if line == "[[event]]" {
+            events.push(EventSettings::default());
+            section = Section::Event;
+        } else if line == "[probe]" {
+            probe.get_or_insert_with(ProbeSettings::default);
+            section = Section::Probe;
+        } else if line == "[state]" {
+            snapshot.get_or_insert_with(Snapshot::new);
+            section = Section::State;
+        } else if let Some(header) = line.strip_prefix("[[").and_then(|l| l.strip_suffix("]]")) {
+            section = open_array(&mut bodies, header.trim()).map_err(error)?;
+        } else if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = open_table(header.trim()).map_err(error)?;
+        } else {
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("se esperaba 'clave = valor'".to_string()))?;
+            let (key, value) = (key.trim(), parse_value(value.trim()).map_err(error)?);
+            match (section, belts.last_mut(), snapshot.as_mut()) {
+                (Section::Belt, Some(belt), _) => assign_belt(belt, key, &value),
+                (Section::Event, _, _) => match events.last_mut() {
+                    Some(event) => assign_event(event, key, &value),
+                    None => Err(format!("'{key}' fuera de una tabla")),
+                },
+                (Section::Probe, _, _) => assign_probe(
+                    probe.get_or_insert_with(ProbeSettings::default),
+                    key,
+                    &value,
+                ),
+                (Section::State, _, Some(snapshot)) => assign_state(snapshot, key, &value),
+                _ => assign(&mut bodies, section, key, &value),
+            }
+            .map_err(error)?;
+        }
+    }
+
+    Ok(Scene {
+        bodies,
+        belts: belts.iter().map(AsteroidBelt::generate).collect(),
+        probe,
+        events,
+        snapshot,
+    })
+}
+
+// Guarda la escena en el formato que lee `parse`, con el estado al final
+pub fn save(
+    path: &str,
+    bodies: &[CelestialBody],
+    belts: &[AsteroidBelt],
+    probe: Option<&ProbeSettings>,
+    events: &[EventSettings],
+    snapshot: &Snapshot,
+) -> Result<(), String> {
+    let mut source = String::from("# Partida guardada\n");
+    for body in bodies {
+        write_body(&mut source, body, 0);
+    }
+    for belt in belts {
+        write_belt(&mut source, &belt.settings);
+    }
+    if let Some(probe) = probe {
+        source.push_str("\n[probe]\n");
+        source.push_str(&format!("route = {}\n", quoted(&probe.route.join(", "))));
+        source.push_str(&format!("speed = {}\n", probe.speed));
+    }
+    for event in events {
+        write_event(&mut source, event);
+    }
+    write_state(&mut source, snapshot);
+    fs::write(path, source).map_err(|e| format!("{path}: {e}"))
+}
+
+fn write_body(source: &mut String, body: &CelestialBody, depth: usize) {
+    let path = format!("body{}", ".moon".repeat(depth));
+    source.push_str(&format!("\n[[{path}]]\n"));
+    source.push_str(&format!("name = {}\n", quoted(&body.name)));
+    source.push_str(&format!("shader = {}\n", quoted(&body.shader)));
+    source.push_str(&format!("scale = {}\n", body.scale));
+    source.push_str(&format!("seed = \"{}\"\n", body.seed));
+    source.push_str(&format!("axial_tilt = {}\n", body.axial_tilt));
+    source.push_str(&format!("rotation_period = {}\n", body.rotation_period));
+    source.push_str(&format!("tail = {}\n", body.tail));
+    source.push_str(&format!("rings = {}\n", body.rings));
+    if let Some(light) = body.light {
+        source.push_str(&format!("light = {}\n", numbers(&[light])));
+    }
+    if let Some(mass) = body.mass {
+        source.push_str(&format!("mass = {mass}\n"));
+    }
+    if let Some(orbit) = body.orbit {
+        source.push_str(&format!("semi_major_axis = {}\n", orbit.semi_major_axis));
+        source.push_str(&format!("eccentricity = {}\n", orbit.eccentricity));
+        source.push_str(&format!("periapsis = {}\n", orbit.periapsis));
+        source.push_str(&format!("inclination = {}\n", orbit.inclination));
+        source.push_str(&format!("ascending_node = {}\n", orbit.ascending_node));
+        source.push_str(&format!("orbit_speed = {}\n", orbit.speed));
+    }
+
+    let mut params = String::new();
+    for (key, value) in body.params.floats() {
+        params.push_str(&format!("{key} = {value}\n"));
+    }
+    for (key, color) in body.params.colors() {
+        params.push_str(&format!("{key} = {}\n", numbers(&[color])));
+    }
+    if !params.is_empty() {
+        source.push_str(&format!("\n[{path}.params]\n{params}"));
+    }
+    for shell in &body.shells {
+        source.push_str(&format!("\n[[{path}.shell]]\n"));
+        source.push_str(&format!("shader = {}\n", quoted(&shell.shader)));
+        source.push_str(&format!("scale = {}\n", shell.scale));
+    }
+    for moon in &body.children {
+        write_body(source, moon, depth + 1);
+    }
+}
+
+fn write_belt(source: &mut String, belt: &BeltSettings) {
+    source.push_str("\n[[belt]]\n");
+    source.push_str(&format!("shader = {}\n", quoted(&belt.shader)));
+    source.push_str(&format!("seed = \"{}\"\n", belt.seed));
+    source.push_str(&format!("count = {}\n", belt.count));
+    source.push_str(&format!("inner_radius = {}\n", belt.inner_radius));
+    source.push_str(&format!("outer_radius = {}\n", belt.outer_radius));
+    source.push_str(&format!("inclination = {}\n", belt.inclination));
+    source.push_str(&format!("eccentricity = {}\n", belt.eccentricity));
+    source.push_str(&format!("size = {}\n", belt.size));
+    source.push_str(&format!("orbit_speed = {}\n", belt.orbit_speed));
+}
+
+fn write_event(source: &mut String, event: &EventSettings) {
+    source.push_str("\n[[event]]\n");
+    source.push_str(&format!("time = {}\n", event.time));
+    if let Some(every) = event.every {
+        source.push_str(&format!("every = {every}\n"));
+    }
+    source.push_str(&format!("action = \"{}\"\n", event.action.name()));
+    if !event.body.is_empty() {
+        source.push_str(&format!("body = {}\n", quoted(&event.body)));
+    }
+    source.push_str(&format!("value = {}\n", event.value));
+}
+
+fn write_state(source: &mut String, snapshot: &Snapshot) {
+    source.push_str("\n[state]\n");
+    source.push_str(&format!("time = {}\n", snapshot.time));
+    source.push_str(&format!("time_scale = {}\n", snapshot.time_scale));
+    source.push_str(&format!("paused = {}\n", snapshot.paused));
+    if let Some(camera) = snapshot.camera {
+        source.push_str(&format!("camera = {}\n", list(&camera.to_numbers())));
+    }
+    let mode = snapshot.camera_mode.name();
+    source.push_str(&format!("camera_mode = \"{mode}\"\n"));
+    if let Some(chase) = snapshot.chase {
+        source.push_str(&format!("chase = {}\n", numbers(&[chase])));
+    }
+    if let Some(seed) = snapshot.seed {
+        source.push_str(&format!("seed = \"{seed}\"\n"));
+    }
+    source.push_str(&format!("selected = {}\n", snapshot.selected));
+    if let Some((positions, velocities)) = &snapshot.gravity {
+        source.push_str(&format!("positions = {}\n", numbers(positions)));
+        source.push_str(&format!("velocities = {}\n", numbers(velocities)));
+    }
+}
+
+// [x, y, z, x, y, z, ...]
+fn numbers(vectors: &[Vector3]) -> String {
+    let flat: Vec<f32> = vectors.iter().flat_map(|v| [v.x, v.y, v.z]).collect();
+    list(&flat)
+}
+
+fn list(values: &[f32]) -> String {
+    let items: Vec<String> = values.iter().map(|number| number.to_string()).collect();
+    format!("[{}]", items.join(", "))
+}
+
+// Texto entre comillas, con `\` delante de las comillas y barras que traiga
+fn quoted(text: &str) -> String {
+    let escaped = text.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{escaped}\"")
+}
+
+// `#` empieza un comentario salvo dentro de un texto
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut chars = line.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => {
+                chars.next();
+            }
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+// "body.moon.moon.shell" -> (2, Some("shell"))
+fn split_path(header: &str) -> Result<(usize, Option<&str>), String> {
+    let mut parts = header.split('.').map(str::trim);
+    if parts.next() != Some("body") {
+        return Err(format!("tabla desconocida '{header}'"));
+    }
+    let mut depth = 0;
+    let mut rest = None;
+    for part in parts {
+        if rest.is_some() {
+            return Err(format!("tabla desconocida '{header}'"));
+        }
+        match part {
+            "moon" => depth += 1,
+            other => rest = Some(other),
+        }
+    }
+    Ok((depth, rest))
+}
+
+fn open_array(bodies: &mut Vec<CelestialBody>, header: &str) -> Result<Section, String> {
+    match split_path(header)? {
+        (0, None) => {
+            bodies.push(CelestialBody::new("", "", 1.0, 0));
+            Ok(Section::Body(0))
+        }
+        (depth, None) => {
+            let parent = last_at(bodies, depth - 1)?;
+            parent.children.push(CelestialBody::new("", "", 1.0, 0));
+            Ok(Section::Body(depth))
+        }
+        (depth, Some("shell")) => {
+            last_at(bodies, depth)?.shells.push(Shell {
+                shader: String::new(),
+                scale: 1.0,
+            });
+            Ok(Section::Shell(depth))
+        }
+        _ => Err(format!("lista desconocida '[[{header}]]'")),
+    }
+}
+
+fn open_table(header: &str) -> Result<Section, String> {
+    match split_path(header)? {
+        (depth, Some("params")) => Ok(Section::Params(depth)),
+        _ => Err(format!("tabla desconocida '[{header}]'")),
+    }
+}
+
+// El último cuerpo declarado en ese nivel del árbol
+fn last_at(bodies: &mut [CelestialBody], depth: usize) -> Result<&mut CelestialBody, String> {
+    let mut body = bodies
+        .last_mut()
+        .ok_or("falta un [[body]] antes".to_string())?;
+    for _ in 0..depth {
+        body = body
+            .children
+            .last_mut()
+            .ok_or("falta la luna del nivel anterior".to_string())?;
+    }
+    Ok(body)
+}
+
+fn assign(
+    bodies: &mut [CelestialBody],
+    section: Section,
+    key: &str,
+    value: &Value,
+) -> Result<(), String> {
+    match section {
+        Section::None | Section::Belt | Section::Event | Section::Probe | Section::State => {
+            Err(format!("'{key}' fuera de una tabla"))
+        }
+        Section::Body(depth) => {
+            let body = last_at(bodies, depth)?;
+            match key {
+                "name" => body.name = value.text()?,
+                "shader" => body.shader = value.text()?,
+                "scale" => body.scale = value.number()?,
+                "seed" => body.seed = value.seed()?,
+                "axial_tilt" => body.axial_tilt = value.number()?,
+                "rotation_period" => body.rotation_period = value.number()?,
+                "tail" => body.tail = value.flag()?,
+                "rings" => body.rings = value.flag()?,
+                "light" => body.light = Some(value.color()?),
+                "mass" => body.mass = Some(value.number()?),
+                "semi_major_axis" => orbit(body).semi_major_axis = value.number()?,
+                "eccentricity" => orbit(body).eccentricity = value.number()?,
+                "periapsis" => orbit(body).periapsis = value.number()?,
+                "inclination" => orbit(body).inclination = value.number()?,
+                "ascending_node" => orbit(body).ascending_node = value.number()?,
+                "orbit_speed" => orbit(body).speed = value.number()?,
+                _ => return Err(format!("clave desconocida '{key}'")),
+            }
+            Ok(())
+        }
+        Section::Shell(depth) => {
+            let body = last_at(bodies, depth)?;
+            let shell = body
+                .shells
+                .last_mut()
+                .ok_or("falta [[shell]]".to_string())?;
+            match key {
+                "shader" => shell.shader = value.text()?,
+                "scale" => shell.scale = value.number()?,
+                _ => return Err(format!("clave desconocida '{key}'")),
+            }
+            Ok(())
+        }
+        Section::Params(depth) => {
+            let body = last_at(bodies, depth)?;
+            let params = std::mem::take(&mut body.params);
+            body.params = match value {
+                Value::Number(number) => params.with_float(key, *number),
+                Value::Numbers(_) => params.with_color(key, value.color()?),
+                _ => return Err(format!("'{key}' debe ser un número o [r, g, b]")),
+            };
+            Ok(())
+        }
+    }
+}
+
+fn assign_belt(belt: &mut BeltSettings, key: &str, value: &Value) -> Result<(), String> {
+    match key {
+        "shader" => belt.shader = value.text()?,
+        "seed" => belt.seed = value.seed()?,
+        "count" => belt.count = value.number()? as u32,
+        "inner_radius" => belt.inner_radius = value.number()?,
+        "outer_radius" => belt.outer_radius = value.number()?,
+        "inclination" => belt.inclination = value.number()?,
+        "eccentricity" => belt.eccentricity = value.number()?,
+        "size" => belt.size = value.number()?,
+        "orbit_speed" => belt.orbit_speed = value.number()?,
+        _ => return Err(format!("clave desconocida '{key}'")),
+    }
+    Ok(())
+}
+
+fn assign_event(event: &mut EventSettings, key: &str, value: &Value) -> Result<(), String> {
+    match key {
+        "time" => event.time = value.number()?,
+        "every" => event.every = Some(value.number()?),
+        "action" => {
+            let name = value.text()?;
+            event.action =
+                EventAction::parse(&name).ok_or_else(|| format!("acción desconocida '{name}'"))?;
+        }
+        "body" => event.body = value.text()?,
+        "value" => event.value = value.number()?,
+        _ => return Err(format!("clave desconocida '{key}'")),
+    }
+    Ok(())
+}
+
+fn assign_probe(probe: &mut ProbeSettings, key: &str, value: &Value) -> Result<(), String> {
+    match key {
+        "route" => {
+            let route = value.text()?;
+            probe.route = route
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .collect();
+        }
+        "speed" => probe.speed = value.number()?,
+        _ => return Err(format!("clave desconocida '{key}'")),
+    }
+    Ok(())
+}
+
+fn assign_state(snapshot: &mut Snapshot, key: &str, value: &Value) -> Result<(), String> {
+    match key {
+        "time" => snapshot.time = value.number()?,
+        "time_scale" => snapshot.time_scale = value.number()?,
+        "paused" => snapshot.paused = value.flag()?,
+        "camera" => snapshot.camera = Some(value.camera()?),
+        "camera_mode" => {
+            let name = value.text()?;
+            snapshot.camera_mode =
+                CameraMode::parse(&name).ok_or(format!("modo de cámara desconocido '{name}'"))?;
+        }
+        "chase" => snapshot.chase = Some(value.color()?),
+        "seed" => snapshot.seed = Some(value.seed()?),
+        "selected" => snapshot.selected = value.number()? as usize,
+        "positions" => gravity(snapshot).0 = value.vectors()?,
+        "velocities" => gravity(snapshot).1 = value.vectors()?,
+        _ => return Err(format!("clave desconocida '{key}'")),
+    }
+    Ok(())
+}
+
+fn gravity(snapshot: &mut Snapshot) -> &mut (Vec<Vector3>, Vec<Vector3>) {
+    snapshot
+        .gravity
+        .get_or_insert_with(|| (Vec::new(), Vec::new()))
+}
+
+fn orbit(body: &mut CelestialBody) -> &mut Orbit {
+    body.orbit.get_or_insert(Orbit {
+        semi_major_axis: 0.0,
+        eccentricity: 0.0,
+        periapsis: 0.0,
+        inclination: 0.0,
+        ascending_node: 0.0,
+        speed: 1.0,
+    })
+}
+
+fn parse_value(text: &str) -> Result<Value, String> {
+    if let Some(inner) = text.strip_prefix('"') {
+        let inner = inner
+            .strip_suffix('"')
+            .ok_or("falta cerrar las comillas".to_string())?;
+        return unescape(inner).map(Value::Text);
+    }
+    if let Some(inner) = text.strip_prefix('[') {
+        let inner = inner
+            .strip_suffix(']')
+            .ok_or("falta cerrar el corchete".to_string())?;
+        let numbers = inner
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(parse_number)
+            .collect::<Result<_, _>>()?;
+        return Ok(Value::Numbers(numbers));
+    }
+    match text {
+        "true" => Ok(Value::Bool(true)),
+        "false" => Ok(Value::Bool(false)),
+        _ => parse_number(text).map(Value::Number),
+    }
+}
+
+// Deshace lo que escribe `quoted`: `\x` es `x` tal cual
+fn unescape(text: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.push(
+                chars
+                    .next()
+                    .ok_or("falta cerrar las comillas".to_string())?,
+            ),
+            '"' => return Err("comillas sin '\\' dentro de un texto".to_string()),
+            c => result.push(c),
+        }
+    }
+    Ok(result)
+}
+
+fn parse_number(text: &str) -> Result<f32, String> {
+    text.parse().map_err(|_| format!("valor inválido '{text}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Guarda y vuelve a leer: las semillas no pasan por f32 y los nombres con
+    // comillas, barras o `#` vuelven iguales
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut planet = CelestialBody::new("El \"Gigante\" #1", "gas_giant", 40.0, u64::MAX);
+        planet.children.push(CelestialBody::new(
+            "Luna \\ Sur",
+            "moon",
+            8.0,
+            (1 << 24) + 1,
+        ));
+        let event = EventSettings {
+            action: EventAction::OrbitRadius,
+            body: planet.name.clone(),
+            value: 520.0,
+            ..EventSettings::default()
+        };
+        let mut snapshot = Snapshot::new();
+        snapshot.seed = Some(u64::MAX - 1);
+
+        let path = std::env::temp_dir().join("lab5_graficas_round_trip.toml");
+        let path = path.to_str().unwrap();
+        save(path, &[planet], &[], None, &[event], &snapshot).unwrap();
+        let scene = load(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let planet = &scene.bodies[0];
+        assert_eq!(planet.name, "El \"Gigante\" #1");
+        assert_eq!(planet.seed, u64::MAX);
+        assert_eq!(planet.children[0].name, "Luna \\ Sur");
+        assert_eq!(planet.children[0].seed, (1 << 24) + 1);
+        assert_eq!(scene.events[0].body, planet.name);
+        assert_eq!(scene.snapshot.unwrap().seed, Some(u64::MAX - 1));
+    }
+}
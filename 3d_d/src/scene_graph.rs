@@ -0,0 +1,148 @@
+use crate::body::{CelestialBody, PlacedBody, Shell};
+use crate::create_model_matrix;
+use raylib::prelude::*;
+
+// Lo que dibuja un nodo; los nodos sin contenido solo agrupan y transforman
+#[derive(Clone, Copy)]
+pub enum NodeContent<'a> {
+    Surface(&'a CelestialBody),
+    // Con el cuerpo al que envuelve, para usar su semilla
+    Shell(&'a CelestialBody, &'a Shell),
+    Rings(&'a CelestialBody),
+}
+
+// Nodo del grafo de escena: una transformación relativa al padre, lo que
+// dibuja y sus hijos. La matriz de mundo se compone al recorrer el árbol, así
+// lo que cuelga de un cuerpo (lunas, atmósferas, anillos) lo acompaña solo.
+pub struct SceneNode<'a> {
+    pub local: Matrix,
+    pub content: Option<NodeContent<'a>>,
+    pub children: Vec<SceneNode<'a>>,
+}
+
+impl<'a> SceneNode<'a> {
+    pub fn new(local: Matrix) -> Self {
+        SceneNode {
+            local,
+            content: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_content(mut self, content: NodeContent<'a>) -> Self {
+        self.content = Some(content);
+        self
+    }
+
+    pub fn with_child(mut self, child: SceneNode<'a>) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    // Preorden, cada nodo con su matriz de mundo. En raylib `a * b` aplica
+    // primero `a`: la transformación local y después la del padre.
+    pub fn traverse(&self, parent: Matrix, visit: &mut impl FnMut(&SceneNode<'a>, Matrix)) {
+        let world = self.local * parent;
+        visit(self, world);
+        for child in &self.children {
+            child.traverse(world, visit);
+        }
+    }
+
+    // Los cuerpos con su posición de mundo, en el orden de `body_mut`
+    pub fn placed_bodies(&self) -> Vec<PlacedBody<'a>> {
+        let mut placed = Vec::new();
+        self.traverse(Matrix::identity(), &mut |node, world| {
+            if let Some(NodeContent::Surface(body)) = node.content {
+                placed.push(PlacedBody {
+                    body,
+                    position: Vector3::new(world.m12, world.m13, world.m14),
+                });
+            }
+        });
+        placed
+    }
+}
+
+// El sistema en el ángulo de simulación `angle`, con la raíz en `center`.
+// Con `simulated` (posiciones de mundo en preorden, de `NBody`) los cuerpos
+// van donde los dejó la gravedad en vez de seguir sus órbitas fijas.
+pub fn build_graph<'a>(
+    bodies: &'a [CelestialBody],
+    center: Vector3,
+    angle: f32,
+    simulated: Option<&[Vector3]>,
+) -> SceneNode<'a> {
+    let root = SceneNode::new(create_model_matrix(center, 1.0, Vector3::zero()));
+    let mut placement = Placement {
+        angle,
+        simulated,
+        next: 0,
+    };
+    bodies.iter().fold(root, |root, body| {
+        root.with_child(body_node(body, center, &mut placement))
+    })
+}
+
+struct Placement<'p> {
+    angle: f32,
+    simulated: Option<&'p [Vector3]>,
+    // Índice en preorden del próximo cuerpo
+    next: usize,
+}
+
+impl Placement<'_> {
+    // Traslación del cuerpo relativa a su padre
+    fn offset(&mut self, body: &CelestialBody, parent: Vector3) -> Vector3 {
+        let index = self.next;
+        self.next += 1;
+        match self.simulated.and_then(|positions| positions.get(index)) {
+            Some(&position) => position - parent,
+            None => body
+                .orbit
+                .map_or(Vector3::zero(), |orbit| orbit.offset(self.angle)),
+        }
+    }
+}
+
+// Cada cuerpo son tres nodos anidados:
+// - órbita: solo la traslación; las lunas cuelgan de aquí para no heredar el
+//   giro ni la inclinación del planeta
+// - eje: inclinación y escala; los anillos cuelgan de aquí y no giran
+// - superficie: el giro sobre el eje; nubes y atmósferas giran con ella
+fn body_node<'a>(
+    body: &'a CelestialBody,
+    parent: Vector3,
+    placement: &mut Placement,
+) -> SceneNode<'a> {
+    // El padre va antes que sus lunas, como en `placed_bodies`
+    let offset = placement.offset(body, parent);
+    let spin = Vector3::new(0.0, body.spin(placement.angle), 0.0);
+    let surface = body.shells.iter().fold(
+        SceneNode::new(create_model_matrix(Vector3::zero(), 1.0, spin))
+            .with_content(NodeContent::Surface(body)),
+        |surface, shell| {
+            surface.with_child(
+                SceneNode::new(create_model_matrix(
+                    Vector3::zero(),
+                    shell.scale,
+                    Vector3::zero(),
+                ))
+                .with_content(NodeContent::Shell(body, shell)),
+            )
+        },
+    );
+
+    let tilt = Vector3::new(body.axial_tilt, 0.0, 0.0);
+    let mut axis =
+        SceneNode::new(create_model_matrix(Vector3::zero(), body.scale, tilt)).with_child(surface);
+    if body.rings {
+        axis = axis
+            .with_child(SceneNode::new(Matrix::identity()).with_content(NodeContent::Rings(body)));
+    }
+
+    let orbit = SceneNode::new(create_model_matrix(offset, 1.0, Vector3::zero())).with_child(axis);
+    body.children.iter().fold(orbit, |orbit, moon| {
+        orbit.with_child(body_node(moon, parent + offset, placement))
+    })
+}
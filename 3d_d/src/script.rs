@@ -0,0 +1,337 @@
+// Lenguaje mínimo para escribir fragment shaders en un archivo de texto y
+// recargarlos sin recompilar. Cada línea es una asignación:
+//
+//     # comentario
+//     let n = fbm(x * 3, y * 3, z * 3) * 0.5 + 0.5
+//     r = mix(0.2, 0.9, n)
+//     g = 0.4
+//     b = 0.3 * n
+//
+// Entradas: x, y, z (dirección en espacio de objeto), u, v (coordenadas de
+// textura) y t (tiempo). Salidas: r, g, b y opcionalmente a.
+
+use crate::noise::{Fbm, Noise, Simplex, Worley};
+
+const INPUTS: [&str; 6] = ["x", "y", "z", "u", "v", "t"];
+const OUTPUTS: [&str; 4] = ["r", "g", "b", "a"];
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Func {
+    Sin,
+    Cos,
+    Abs,
+    Floor,
+    Fract,
+    Sqrt,
+    Pow,
+    Min,
+    Max,
+    Clamp,
+    Mix,
+    Smoothstep,
+    Noise,
+    Fbm,
+    Cells,
+}
+
+impl Func {
+    fn parse(name: &str) -> Option<(Func, usize)> {
+        let func = match name {
+            "sin" => (Func::Sin, 1),
+            "cos" => (Func::Cos, 1),
+            "abs" => (Func::Abs, 1),
+            "floor" => (Func::Floor, 1),
+            "fract" => (Func::Fract, 1),
+            "sqrt" => (Func::Sqrt, 1),
+            "pow" => (Func::Pow, 2),
+            "min" => (Func::Min, 2),
+            "max" => (Func::Max, 2),
+            "clamp" => (Func::Clamp, 3),
+            "mix" => (Func::Mix, 3),
+            "smoothstep" => (Func::Smoothstep, 3),
+            "noise" => (Func::Noise, 3),
+            "fbm" => (Func::Fbm, 3),
+            "cells" => (Func::Cells, 3),
+            _ => return None,
+        };
+        Some(func)
+    }
+}
+
+#[derive(Debug)]
+enum Expr {
+    Number(f32),
+    Slot(usize),
+    Negate(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(Func, Vec<Expr>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f32),
+    Name(String),
+    Symbol(char),
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut text = String::new();
+            while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit() || **d == '.') {
+                text.push(d);
+                chars.next();
+            }
+            let value = text
+                .parse()
+                .map_err(|_| format!("número inválido '{text}'"))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut text = String::new();
+            while let Some(&d) = chars.peek().filter(|d| d.is_alphanumeric() || **d == '_') {
+                text.push(d);
+                chars.next();
+            }
+            tokens.push(Token::Name(text));
+        } else if "+-*/(),=".contains(c) {
+            tokens.push(Token::Symbol(c));
+            chars.next();
+        } else {
+            return Err(format!("carácter inesperado '{c}'"));
+        }
+    }
+    Ok(tokens)
+}
+
+// Parser recursivo descendente de una expresión; los nombres se resuelven a
+// índices de la tabla de variables al parsear
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    names: &'a [String],
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), String> {
+        match self.next() {
+            Some(Token::Symbol(c)) if c == symbol => Ok(()),
+            _ => Err(format!("se esperaba '{symbol}'")),
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expr, String> {
+        let mut left = self.term()?;
+        while let Some(Token::Symbol(c @ ('+' | '-'))) = self.peek() {
+            let op = if *c == '+' { Op::Add } else { Op::Sub };
+            self.position += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.term()?));
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut left = self.factor()?;
+        while let Some(Token::Symbol(c @ ('*' | '/'))) = self.peek() {
+            let op = if *c == '*' { Op::Mul } else { Op::Div };
+            self.position += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.factor()?));
+        }
+        Ok(left)
+    }
+
+    fn factor(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::Symbol('-')) => Ok(Expr::Negate(Box::new(self.factor()?))),
+            Some(Token::Symbol('(')) => {
+                let inner = self.expression()?;
+                self.expect(')')?;
+                Ok(inner)
+            }
+            Some(Token::Name(name)) => {
+                if self.peek() == Some(&Token::Symbol('(')) {
+                    self.call(&name)
+                } else {
+                    self.names
+                        .iter()
+                        .position(|known| *known == name)
+                        .map(Expr::Slot)
+                        .ok_or(format!("variable desconocida '{name}'"))
+                }
+            }
+            _ => Err("expresión incompleta".to_string()),
+        }
+    }
+
+    fn call(&mut self, name: &str) -> Result<Expr, String> {
+        let (func, arity) = Func::parse(name).ok_or(format!("función desconocida '{name}'"))?;
+        self.expect('(')?;
+        let mut args = vec![self.expression()?];
+        while self.peek() == Some(&Token::Symbol(',')) {
+            self.position += 1;
+            args.push(self.expression()?);
+        }
+        self.expect(')')?;
+        if args.len() != arity {
+            return Err(format!("'{name}' recibe {arity} argumentos"));
+        }
+        Ok(Expr::Call(func, args))
+    }
+}
+
+// Un script ya parseado: asignaciones en orden sobre una tabla de variables
+// que empieza con las entradas y las salidas
+#[derive(Debug)]
+pub struct Program {
+    names: Vec<String>,
+    statements: Vec<(usize, Expr)>,
+}
+
+impl Program {
+    pub fn parse(source: &str) -> Result<Program, String> {
+        let mut names: Vec<String> = INPUTS
+            .iter()
+            .chain(&OUTPUTS)
+            .map(|s| s.to_string())
+            .collect();
+        let mut statements = Vec::new();
+
+        for (index, line) in source.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: String| format!("línea {}: {message}", index + 1);
+
+            let tokens = tokenize(line).map_err(error)?;
+            let (is_let, rest) = match tokens.first() {
+                Some(Token::Name(word)) if word == "let" => (true, &tokens[1..]),
+                _ => (false, &tokens[..]),
+            };
+            let (name, expression) = match rest {
+                [Token::Name(name), Token::Symbol('='), expression @ ..] => (name, expression),
+                _ => return Err(error("se esperaba 'nombre = expresión'".to_string())),
+            };
+            if INPUTS.contains(&name.as_str()) {
+                return Err(error(format!(
+                    "'{name}' es una entrada y no se puede asignar"
+                )));
+            }
+
+            let mut parser = Parser {
+                tokens: expression,
+                position: 0,
+                names: &names,
+            };
+            let expr = parser.expression().map_err(error)?;
+            if parser.position != expression.len() {
+                return Err(error("sobra texto al final".to_string()));
+            }
+
+            let slot = match names.iter().position(|known| known == name) {
+                Some(slot) => slot,
+                None if is_let => {
+                    names.push(name.clone());
+                    names.len() - 1
+                }
+                None => {
+                    return Err(error(format!(
+                        "variable desconocida '{name}' (falta 'let')"
+                    )));
+                }
+            };
+            statements.push((slot, expr));
+        }
+
+        Ok(Program { names, statements })
+    }
+
+    // Devuelve (r, g, b, a); el alfa vale 1 si el script no lo asigna
+    pub fn run(&self, inputs: [f32; 6], noise: &ScriptNoise) -> [f32; 4] {
+        let mut values = vec![0.0; self.names.len()];
+        values[..INPUTS.len()].copy_from_slice(&inputs);
+        values[INPUTS.len() + 3] = 1.0;
+
+        for (slot, expr) in &self.statements {
+            values[*slot] = evaluate(expr, &values, noise);
+        }
+
+        let outputs = &values[INPUTS.len()..INPUTS.len() + OUTPUTS.len()];
+        [outputs[0], outputs[1], outputs[2], outputs[3]]
+    }
+}
+
+// Las fuentes de ruido que los scripts pueden llamar
+pub struct ScriptNoise {
+    pub simplex: Simplex,
+    pub cells: Worley,
+}
+
+fn evaluate(expr: &Expr, values: &[f32], noise: &ScriptNoise) -> f32 {
+    match expr {
+        Expr::Number(value) => *value,
+        Expr::Slot(slot) => values[*slot],
+        Expr::Negate(inner) => -evaluate(inner, values, noise),
+        Expr::Binary(op, left, right) => {
+            let (a, b) = (
+                evaluate(left, values, noise),
+                evaluate(right, values, noise),
+            );
+            match op {
+                Op::Add => a + b,
+                Op::Sub => a - b,
+                Op::Mul => a * b,
+                Op::Div => a / b,
+            }
+        }
+        Expr::Call(func, args) => {
+            let arg: Vec<f32> = args
+                .iter()
+                .map(|arg| evaluate(arg, values, noise))
+                .collect();
+            match func {
+                Func::Sin => arg[0].sin(),
+                Func::Cos => arg[0].cos(),
+                Func::Abs => arg[0].abs(),
+                Func::Floor => arg[0].floor(),
+                Func::Fract => arg[0].fract(),
+                Func::Sqrt => arg[0].sqrt(),
+                Func::Pow => arg[0].powf(arg[1]),
+                Func::Min => arg[0].min(arg[1]),
+                Func::Max => arg[0].max(arg[1]),
+                // Sin `f32::clamp`, que entra en pánico si el script invierte los límites
+                Func::Clamp => arg[0].max(arg[1]).min(arg[2]),
+                Func::Mix => arg[0] + (arg[1] - arg[0]) * arg[2],
+                Func::Smoothstep => {
+                    let x = ((arg[2] - arg[0]) / (arg[1] - arg[0])).clamp(0.0, 1.0);
+                    x * x * (3.0 - 2.0 * x)
+                }
+                Func::Noise => noise.simplex.noise3(arg[0], arg[1], arg[2]),
+                Func::Fbm => Fbm::new(4).sample3(&noise.simplex, arg[0], arg[1], arg[2]),
+                Func::Cells => noise.cells.cells3(arg[0], arg[1], arg[2]).f1,
+            }
+        }
+    }
+}
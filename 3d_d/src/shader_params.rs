@@ -0,0 +1,60 @@
+use raylib::prelude::*;
+
+// Valores con nombre que un draw le pasa a su shader, para que un mismo
+// shader dé planetas distintos a partir de datos. Si falta un nombre, el
+// shader usa su propio valor por defecto.
+#[derive(Clone, Debug, Default)]
+pub struct ShaderParams {
+    floats: Vec<(String, f32)>,
+    colors: Vec<(String, Vector3)>,
+}
+
+// Parámetros vacíos para los draws que no configuran nada
+pub static NO_PARAMS: ShaderParams = ShaderParams::new();
+
+impl ShaderParams {
+    pub const fn new() -> Self {
+        ShaderParams {
+            floats: Vec::new(),
+            colors: Vec::new(),
+        }
+    }
+
+    pub fn with_float(mut self, name: &str, value: f32) -> Self {
+        self.floats.retain(|(key, _)| key != name);
+        self.floats.push((name.to_string(), value));
+        self
+    }
+
+    pub fn with_color(mut self, name: &str, value: Vector3) -> Self {
+        self.colors.retain(|(key, _)| key != name);
+        self.colors.push((name.to_string(), value));
+        self
+    }
+
+    pub fn floats(&self) -> impl Iterator<Item = (&str, f32)> {
+        self.floats
+            .iter()
+            .map(|(key, value)| (key.as_str(), *value))
+    }
+
+    pub fn colors(&self) -> impl Iterator<Item = (&str, Vector3)> {
+        self.colors
+            .iter()
+            .map(|(key, value)| (key.as_str(), *value))
+    }
+
+    pub fn float(&self, name: &str, default: f32) -> f32 {
+        self.floats
+            .iter()
+            .find(|(key, _)| key == name)
+            .map_or(default, |&(_, value)| value)
+    }
+
+    pub fn color(&self, name: &str, default: Vector3) -> Vector3 {
+        self.colors
+            .iter()
+            .find(|(key, _)| key == name)
+            .map_or(default, |&(_, value)| value)
+    }
+}
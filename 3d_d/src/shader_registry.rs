@@ -0,0 +1,55 @@
+use crate::shaders::FragmentShader;
+
+// Shaders disponibles por nombre, en orden de registro, para asignarlos a los
+// cuerpos desde archivos de escena o cambiarlos en tiempo de ejecución
+pub struct ShaderRegistry<'a> {
+    entries: Vec<(&'static str, &'a dyn FragmentShader)>,
+    // Para los nombres que no están registrados
+    fallback: Option<&'a dyn FragmentShader>,
+}
+
+impl<'a> ShaderRegistry<'a> {
+    pub fn new() -> Self {
+        ShaderRegistry {
+            entries: Vec::new(),
+            fallback: None,
+        }
+    }
+
+    pub fn with_fallback(mut self, shader: &'a dyn FragmentShader) -> Self {
+        self.fallback = Some(shader);
+        self
+    }
+
+    // Registrar dos veces el mismo nombre reemplaza al anterior
+    pub fn with(mut self, name: &'static str, shader: &'a dyn FragmentShader) -> Self {
+        match self.entries.iter_mut().find(|(known, _)| *known == name) {
+            Some(entry) => entry.1 = shader,
+            None => self.entries.push((name, shader)),
+        }
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&'a dyn FragmentShader> {
+        self.entries
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, shader)| *shader)
+    }
+
+    // Como `get`, pero un nombre desconocido da el shader de reemplazo
+    pub fn resolve(&self, name: &str) -> Option<&'a dyn FragmentShader> {
+        self.get(name).or(self.fallback)
+    }
+
+    // El nombre que sigue a `name`, volviendo al primero al final; un nombre
+    // desconocido da el primero
+    pub fn next_name(&self, name: &str) -> &'static str {
+        let index = self
+            .entries
+            .iter()
+            .position(|(known, _)| *known == name)
+            .map_or(0, |index| index + 1);
+        self.entries[index % self.entries.len()].0
+    }
+}
@@ -1,9 +1,22 @@
 use crate::Uniforms;
+use crate::bump::{perturbed_normal, to_world};
+use crate::color_ramp::{ColorRamp, Interpolation};
+use crate::fragment::Fragment;
+use crate::lighting::specular;
+use crate::noise::{DomainWarp, Fbm, Noise, Perlin, Simplex, Worley, seed_offset};
+use crate::script::{Program, ScriptNoise};
+use crate::texture::{Cubemap, Sampler, Texture, Wrap};
 use crate::vertex::Vertex;
+use crate::vertex_output::{Varyings, VertexOutput};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use raylib::prelude::*;
+use std::f32::consts::PI;
+use std::fs;
+use std::time::SystemTime;
 
 // ==========================================
-// === Transformación del vértice (igual) ===
+// ===     Transformación del vértice     ===
 // ==========================================
 fn multiply_matrix_vector4(matrix: &Matrix, vector: &Vector4) -> Vector4 {
     Vector4::new(
@@ -14,27 +27,49 @@ fn multiply_matrix_vector4(matrix: &Matrix, vector: &Vector4) -> Vector4 {
     )
 }
 
-pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> VertexOutput {
     let position_vec4 = Vector4::new(vertex.position.x, vertex.position.y, vertex.position.z, 1.0);
-    let transformed_vec4 = multiply_matrix_vector4(&uniforms.model_matrix, &position_vec4);
+    let world_position = multiply_matrix_vector4(&uniforms.model_matrix, &position_vec4);
+    let view_position = multiply_matrix_vector4(&uniforms.view_matrix, &world_position);
+    let clip_position = multiply_matrix_vector4(&uniforms.projection_matrix, &view_position);
 
-    let transformed_position = if transformed_vec4.w != 0.0 {
-        Vector3::new(
-            transformed_vec4.x / transformed_vec4.w,
-            transformed_vec4.y / transformed_vec4.w,
-            transformed_vec4.z / transformed_vec4.w,
+    // w = 0 para que la traslación no afecte a la normal
+    let normal_vec4 = Vector4::new(vertex.normal.x, vertex.normal.y, vertex.normal.z, 0.0);
+    let world_normal = multiply_matrix_vector4(&uniforms.model_matrix, &normal_vec4);
+
+    let varyings = Varyings {
+        normal: Vector3::new(world_normal.x, world_normal.y, world_normal.z).normalized(),
+        tex_coords: vertex.tex_coords,
+        world_position: Vector3::new(world_position.x, world_position.y, world_position.z),
+        object_position: vertex.position,
+        color: vertex.color,
+        ..Varyings::default()
+    };
+
+    VertexOutput::new(clip_position, varyings)
+}
+
+// Se aplica después del recorte: clip space -> NDC -> pantalla
+pub fn viewport_transform(vertex: &VertexOutput, uniforms: &Uniforms) -> VertexOutput {
+    let clip_position = vertex.clip_position;
+
+    // División de perspectiva: clip space -> NDC
+    let ndc = if clip_position.w != 0.0 {
+        Vector4::new(
+            clip_position.x / clip_position.w,
+            clip_position.y / clip_position.w,
+            clip_position.z / clip_position.w,
+            1.0,
         )
     } else {
-        Vector3::new(transformed_vec4.x, transformed_vec4.y, transformed_vec4.z)
+        Vector4::new(clip_position.x, clip_position.y, clip_position.z, 1.0)
     };
 
-    Vertex {
-        position: vertex.position,
-        normal: vertex.normal,
-        tex_coords: vertex.tex_coords,
-        color: vertex.color,
-        transformed_position,
-        transformed_normal: vertex.normal,
+    let screen_position = multiply_matrix_vector4(&uniforms.viewport_matrix, &ndc);
+
+    VertexOutput {
+        screen_position: Vector3::new(screen_position.x, screen_position.y, screen_position.z),
+        ..vertex.clone()
     }
 }
 
@@ -42,37 +77,1283 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
 // === Shaders personalizados por esfera ===
 // ==========================================
 
-fn simple_noise(x: f32, y: f32, z: f32) -> f32 {
-    ((x * 12.9898 + y * 78.233 + z * 37.719).sin() * 43758.5453).fract()
+pub trait FragmentShader {
+    // Devuelve RGBA; el alfa solo se usa si el draw tiene la mezcla activada
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector4;
+
+    // Lo que emite luz propia (la estrella) no pasa por la iluminación y se
+    // escribe con todo su brillo
+    fn emissive(&self) -> bool {
+        false
+    }
+
+    // Luz propia que se suma después de iluminar, como luces nocturnas o
+    // magma; no depende del sol
+    fn emission(&self, _fragment: &Fragment, _uniforms: &Uniforms) -> Vector3 {
+        Vector3::zero()
+    }
+
+    // Normal en mundo con la que se ilumina el fragmento; los shaders con
+    // relieve la inclinan según su altura (ver `bump.rs`)
+    fn normal(&self, fragment: &Fragment, _uniforms: &Uniforms) -> Vector3 {
+        fragment.normal
+    }
+}
+
+// Shader vacío para la pasada de solo profundidad
+pub struct DepthOnlyShader;
+
+impl FragmentShader for DepthOnlyShader {
+    fn shade(&self, _fragment: &Fragment, _uniforms: &Uniforms) -> Vector4 {
+        Vector4::new(0.0, 0.0, 0.0, 0.0)
+    }
+}
+
+// Cobertura de casquete polar en [0, 1]. La latitud sale de la dirección en
+// espacio de objeto; `size` es la fracción de cada hemisferio que cubre
+// (0 = sin casquete) y el ruido en `q` deshilacha el borde.
+pub fn polar_cap(dir: Vector3, noise: &impl Noise, q: Vector3, size: f32) -> f32 {
+    if size <= 0.0 {
+        return 0.0;
+    }
+    let latitude = dir.normalized().y.clamp(-1.0, 1.0).asin().abs() / (PI * 0.5);
+    let edge = 1.0 - size + noise.noise3(q.x * 4.0, q.y * 4.0, q.z * 4.0) * 0.08;
+    ((latitude - edge) / 0.03).clamp(0.0, 1.0)
 }
 
-pub fn star_shader(pos: &Vector3) -> Vector3 {
-    // Calculamos un “ruido” basado en la posición
-    let n = simple_noise(pos.x * 0.1, pos.y * 0.1, pos.z * 0.1);
-    // Mezclamos variación de color
-    let base = Vector3::new(1.0, 0.94, 0.6);
-    let variation = Vector3::new(0.2 * n, 0.1 * n, 0.05 * n);
-    let color = base + variation;
-    // Emisión/un brillo extra — podrías multiplicar por un factor para que parezca que “emite”
-    let brightness = 1.0 + n * 0.5;
-    color * brightness
+// Mancha solar: posición en la esfera (radianes), tamaño angular y
+// velocidad con la que avanza en longitud
+struct Sunspot {
+    latitude: f32,
+    longitude: f32,
+    radius: f32,
+    drift: f32,
+}
+
+impl Sunspot {
+    fn direction(&self, time: f32) -> Vector3 {
+        let longitude = self.longitude + self.drift * time;
+        Vector3::new(
+            self.latitude.cos() * longitude.cos(),
+            self.latitude.sin(),
+            self.latitude.cos() * longitude.sin(),
+        )
+    }
+}
+
+// ⭐ Estrella con brillo variable
+pub struct StarShader {
+    noise: Perlin,
+    cells: Worley,
+    sunspots: Vec<Sunspot>,
+}
+
+impl StarShader {
+    pub fn new(seed: u64) -> Self {
+        StarShader {
+            noise: Perlin::new(seed),
+            cells: Worley::new(seed.wrapping_add(1)),
+            sunspots: generate_sunspots(seed.wrapping_add(2)),
+        }
+    }
+
+    // 0 en el centro de una mancha, 1 fuera de todas
+    fn sunspot_factor(&self, dir: Vector3, time: f32) -> f32 {
+        self.sunspots.iter().fold(1.0, |factor, spot| {
+            let distance = dir.dot(spot.direction(time)).clamp(-1.0, 1.0).acos();
+            let shade = if distance < spot.radius * 0.5 {
+                // Umbra: núcleo oscuro
+                0.2
+            } else if distance < spot.radius {
+                // Penumbra: se aclara hacia afuera
+                let edge = (distance - spot.radius * 0.5) / (spot.radius * 0.5);
+                0.55 + 0.45 * edge
+            } else {
+                1.0
+            };
+            factor.min(shade)
+        })
+    }
+}
+
+// Las manchas aparecen en grupos cerca del ecuador; las de latitudes bajas
+// avanzan más rápido, como la rotación diferencial del Sol
+fn generate_sunspots(seed: u64) -> Vec<Sunspot> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut sunspots = Vec::new();
+    for _ in 0..rng.gen_range(3..6) {
+        let latitude: f32 = rng.gen_range(-0.6..0.6);
+        let longitude: f32 = rng.gen_range(0.0..2.0 * PI);
+        let drift = 0.02 * (1.0 - 0.5 * latitude.sin().powi(2));
+        for _ in 0..rng.gen_range(1..4) {
+            sunspots.push(Sunspot {
+                latitude: latitude + rng.gen_range(-0.08..0.08),
+                longitude: longitude + rng.gen_range(-0.15..0.15),
+                radius: rng.gen_range(0.03..0.08),
+                drift,
+            });
+        }
+    }
+    sunspots
+}
+
+impl FragmentShader for StarShader {
+    fn emissive(&self) -> bool {
+        true
+    }
+
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector4 {
+        // En espacio de objeto el patrón queda pegado a la esfera
+        let dir = fragment.object_position.normalized();
+        let q = dir + seed_offset(uniforms.seed);
+        let t = uniforms.time;
+
+        // Granulación: celdas de convección que se desplazan lentamente; los
+        // bordes entre celdas (F2 - F1 chico) son más oscuros
+        let cells = self
+            .cells
+            .cells3(q.x * 12.0, q.y * 12.0 + t * 0.05, q.z * 12.0 + t * 0.03);
+        let cell_edge = ((cells.f2 - cells.f1) * 4.0).min(1.0);
+
+        // Turbulencia 3D que evoluciona con el tiempo, llevada a [0, 1]
+        let n = Fbm::new(4).sample3(
+            &self.noise,
+            q.x * 4.0 + t * 0.1,
+            q.y * 4.0,
+            q.z * 4.0 - t * 0.1,
+        ) * 0.5
+            + 0.5;
+        let flicker = 1.0 + 0.05 * (t * 3.0 + n * 10.0).sin();
+
+        let base = uniforms
+            .params
+            .color("star_color", Vector3::new(1.0, 0.94, 0.6));
+        let variation = Vector3::new(0.2 * n, 0.1 * n, 0.05 * n);
+        let brightness = (1.0 + n * 0.3) * (0.8 + 0.2 * cell_edge) * flicker;
+
+        // Oscurecimiento hacia el limbo: en la silueta se ve gas más frío,
+        // más oscuro y más rojizo
+        let to_camera = (uniforms.camera_position - fragment.world_position).normalized();
+        let mu = fragment.normal.dot(to_camera).clamp(0.0, 1.0);
+        let limb = 0.4 + 0.6 * mu.powf(0.5);
+        let limb_tint = Vector3::new(1.0, 0.75 + 0.25 * mu, 0.5 + 0.5 * mu);
+
+        let spots = self.sunspot_factor(dir, t);
+        let color = (base + variation) * brightness * limb * spots;
+        Vector4::new(
+            color.x * limb_tint.x,
+            color.y * limb_tint.y,
+            color.z * limb_tint.z,
+            1.0,
+        )
+    }
 }
 
 // 🪨 Planeta rocoso rojizo con relieve
-pub fn rocky_shader(pos: &Vector3) -> Vector3 {
-    let noise = ((pos.x * 0.3).sin() * (pos.y * 0.7).cos() * (pos.z * 0.3).sin()) * 0.5 + 0.5;
-    let r = 0.64 + noise * 0.35;
-    let g = 0.25 + noise * 0.2;
-    let b = 0.2;
-    Vector3::new(r, g, b)
-}
-
-// ☁️ Planeta gaseoso celeste con remolino
-pub fn gas_shader(pos: &Vector3) -> Vector3 {
-    let swirl = ((pos.x * 0.15).sin() + (pos.y * 0.2).cos()) * 0.5 + 0.5;
-    let storm = ((pos.x * 0.3 + pos.y * 0.3).sin() * 0.5 + 0.5) * swirl;
-    let r = 0.25 + 0.1 * storm;
-    let g = 0.6 + 0.2 * storm;
-    let b = 0.9 + 0.1 * swirl;
-    Vector3::new(r, g, b)
+// Cuánto inclina la normal la pendiente del relieve
+const ROCKY_RELIEF: f32 = 0.15;
+
+// Simplex en vez de Perlin: sin artefactos alineados a los ejes en los polos
+pub struct RockyShader {
+    noise: Simplex,
+    craters: Worley,
+}
+
+impl RockyShader {
+    pub fn new(seed: u64) -> Self {
+        RockyShader {
+            noise: Simplex::new(seed),
+            craters: Worley::new(seed.wrapping_add(1)),
+        }
+    }
+
+    // Relieve en [0, 1]: colinas de fBm y cráteres hundidos con borde alto.
+    // `p` es la dirección ya desplazada por la semilla del cuerpo. El perfil
+    // es continuo (el fondo sube hasta el borde y el borde baja al terreno)
+    // para que las diferencias finitas de la normal no den saltos.
+    fn height(&self, p: Vector3, crater_scale: f32) -> f32 {
+        let hills = Fbm::new(5).sample3(&self.noise, p.x * 4.0, p.y * 4.0, p.z * 4.0) * 0.5 + 0.5;
+        let c = p * crater_scale;
+        let f1 = self.craters.cells3(c.x, c.y, c.z).f1;
+        let crater = if f1 < 0.25 {
+            -0.15 + 0.23 * smoothstep(0.0, 0.25, f1)
+        } else {
+            0.08 * (1.0 - smoothstep(0.25, 0.32, f1))
+        };
+        hills + crater
+    }
+}
+
+// Parámetros: `base_color` y `height_color` (cuánto cambia el color con la
+// altura; por defecto un planeta rojizo tipo Marte), `crater_scale` y
+// `polar_cap`
+impl FragmentShader for RockyShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector4 {
+        let params = uniforms.params;
+        let base_color = params.color("base_color", Vector3::new(0.64, 0.25, 0.2));
+        let height_color = params.color("height_color", Vector3::new(0.35, 0.2, 0.0));
+        let crater_scale = params.float("crater_scale", 5.0);
+
+        let dir = fragment.object_position.normalized();
+        let offset = seed_offset(uniforms.seed);
+        let height = self.height(dir + offset, crater_scale);
+
+        let color = base_color + height_color * height;
+        let cap = polar_cap(
+            dir,
+            &self.noise,
+            dir + offset,
+            params.float("polar_cap", 0.0),
+        );
+        let color = color.lerp(Vector3::new(0.9, 0.88, 0.86), cap);
+        Vector4::new(color.x, color.y, color.z, 1.0)
+    }
+
+    // Las laderas y los bordes de los cráteres responden a la luz
+    fn normal(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
+        let crater_scale = uniforms.params.float("crater_scale", 5.0);
+        let offset = seed_offset(uniforms.seed);
+        let bumped = perturbed_normal(fragment.object_position, ROCKY_RELIEF, |d| {
+            self.height(d + offset, crater_scale)
+        });
+        to_world(&uniforms.model_matrix, bumped)
+    }
+}
+
+// Tormenta ovalada fija a una latitud; se mueve con la banda que la contiene
+struct Vortex {
+    latitude: f32,
+    longitude: f32,
+    width: f32,
+    height: f32,
+}
+
+// Gran tormenta anticiclónica (como la Gran Mancha Roja), fija al planeta.
+// Latitud y longitud en radianes; el radio es angular y el óvalo es más
+// ancho que alto.
+#[derive(Clone, Copy)]
+pub struct GreatStorm {
+    pub latitude: f32,
+    pub longitude: f32,
+    pub radius: f32,
+    pub color: Vector3,
+}
+
+impl GreatStorm {
+    fn center(&self) -> Vector3 {
+        Vector3::new(
+            self.latitude.cos() * self.longitude.cos(),
+            self.latitude.sin(),
+            self.latitude.cos() * self.longitude.sin(),
+        )
+    }
+
+    // Distancia elíptica al centro: 1 en el borde del óvalo
+    fn distance(&self, dir: Vector3) -> f32 {
+        let latitude = dir.y.clamp(-1.0, 1.0).asin();
+        let longitude = dir.z.atan2(dir.x);
+        let delta = (longitude - self.longitude + PI).rem_euclid(2.0 * PI) - PI;
+        let dx = delta * self.latitude.cos() / (self.radius * 1.8);
+        let dy = (latitude - self.latitude) / self.radius;
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    // Tuerce la dirección alrededor del centro; el giro se apaga lejos de
+    // la tormenta, así arrastra las bandas vecinas
+    fn swirl(&self, dir: Vector3, time: f32) -> Vector3 {
+        let r = self.distance(dir);
+        if r > 3.0 {
+            return dir;
+        }
+        let falloff = (1.0 - r / 3.0).powi(2);
+        let angle = falloff * (1.5 + time * 0.2);
+        rotate_around(dir, self.center(), angle)
+    }
+}
+
+// Rotación de Rodrigues de `v` alrededor del eje unitario `axis`
+fn rotate_around(v: Vector3, axis: Vector3, angle: f32) -> Vector3 {
+    let (sin, cos) = angle.sin_cos();
+    v * cos + axis.cross(v) * sin + axis * (axis.dot(v) * (1.0 - cos))
+}
+
+// ☁️ Gigante gaseoso con bandas turbulentas y tormentas ovaladas
+pub struct GasShader {
+    noise: Simplex,
+    warp: DomainWarp,
+    bands: f32,
+    light: Vector3,
+    dark: Vector3,
+    vortices: Vec<Vortex>,
+    storm: Option<GreatStorm>,
+}
+
+impl GasShader {
+    // `bands` es cuántas franjas hay de polo a polo y `turbulence` cuánto se
+    // deforman; los colores y las tormentas cambian de un planeta a otro
+    pub fn new(seed: u64, bands: f32, turbulence: f32, light: Vector3, dark: Vector3) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed.wrapping_add(1));
+        let vortices = (0..rng.gen_range(1..4))
+            .map(|_| Vortex {
+                latitude: rng.gen_range(-0.6..0.6),
+                longitude: rng.gen_range(0.0..2.0 * PI),
+                width: rng.gen_range(0.2..0.45),
+                height: rng.gen_range(0.05..0.1),
+            })
+            .collect();
+
+        GasShader {
+            noise: Simplex::new(seed),
+            warp: DomainWarp::new(3, turbulence),
+            bands,
+            light,
+            dark,
+            vortices,
+            storm: None,
+        }
+    }
+
+    pub fn with_storm(mut self, storm: GreatStorm) -> Self {
+        self.storm = Some(storm);
+        self
+    }
+
+    // Intensidad de tormenta en [0, 1] y anillos del remolino
+    fn vortex(&self, latitude: f32, longitude: f32, time: f32) -> (f32, f32) {
+        self.vortices
+            .iter()
+            .fold((0.0, 0.0), |(strength, rings), vortex| {
+                let band_longitude = longitude - self.band_spin(vortex.latitude) * time;
+                let delta = (band_longitude - vortex.longitude + PI).rem_euclid(2.0 * PI) - PI;
+                let dx = delta / vortex.width;
+                let dy = (latitude - vortex.latitude) / vortex.height;
+                let r = (dx * dx + dy * dy).sqrt();
+                if r < 1.0 && 1.0 - r > strength {
+                    // Los anillos giran alrededor del centro
+                    let angle = dy.atan2(dx);
+                    (1.0 - r, (r * 9.0 + angle - time * 0.8).sin() * 0.5 + 0.5)
+                } else {
+                    (strength, rings)
+                }
+            })
+    }
+
+    // Bandas vecinas giran en sentidos opuestos
+    fn band_spin(&self, latitude: f32) -> f32 {
+        let band = ((latitude + 1.0) * 0.5 * self.bands).floor() as i32;
+        if band % 2 == 0 { 0.04 } else { -0.03 }
+    }
+}
+
+impl FragmentShader for GasShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector4 {
+        // En espacio de objeto las bandas no se deslizan mientras orbita
+        let dir = fragment.object_position.normalized();
+        let t = uniforms.time;
+        let latitude = dir.y;
+        let dir = match &self.storm {
+            Some(storm) => storm.swirl(dir, t),
+            None => dir,
+        };
+
+        // Cada banda rota alrededor del eje Y con su propia velocidad
+        let (sin, cos) = (self.band_spin(latitude) * t).sin_cos();
+        let p = Vector3::new(dir.x * cos - dir.z * sin, dir.y, dir.x * sin + dir.z * cos);
+
+        // Las bandas siguen la latitud deformada, así se arremolinan
+        let warped = self
+            .warp
+            .warp3(&self.noise, (p + seed_offset(uniforms.seed)) * 2.0);
+        let stripe = (warped.y * self.bands * PI * 0.5).sin() * 0.5 + 0.5;
+        let detail =
+            Fbm::new(3).sample3(&self.noise, warped.x * 2.0, warped.y * 6.0, warped.z * 2.0) * 0.5
+                + 0.5;
+        let color = self.dark.lerp(self.light, stripe * 0.8 + detail * 0.2);
+
+        // Tormentas: anillos que mezclan los dos tonos con un toque rojizo
+        let longitude = dir.z.atan2(dir.x);
+        let (storm, rings) = self.vortex(latitude, longitude, t);
+        let storm_color = Vector3::new(0.75, 0.35, 0.25).lerp(self.light, rings * 0.6);
+        let color = color.lerp(storm_color, storm.sqrt());
+
+        // Neblina pálida sobre los polos
+        let cap = polar_cap(
+            fragment.object_position,
+            &self.noise,
+            p,
+            uniforms.params.float("polar_cap", 0.0),
+        );
+        let color = color.lerp(self.light * 1.1, cap * 0.6);
+
+        // La gran tormenta tapa las bandas con su propio color y anillos
+        let color = match &self.storm {
+            Some(great) => {
+                let r = great.distance(fragment.object_position.normalized());
+                if r < 1.0 {
+                    let ring = (r * 12.0 - t * 0.5).sin() * 0.5 + 0.5;
+                    let inner = great.color * (0.85 + 0.15 * ring);
+                    color.lerp(inner, (1.0 - r).sqrt().min(1.0))
+                } else {
+                    color
+                }
+            }
+            None => color,
+        };
+
+        Vector4::new(color.x, color.y, color.z, 1.0)
+    }
+}
+
+// 🌍 Planeta tipo Tierra: océanos, continentes, costas y casquetes polares
+pub struct EarthShader {
+    noise: Simplex,
+    cities: Worley,
+    // Color según la altura: fondo oceánico, costa, llanuras y montañas
+    palette: ColorRamp,
+    // Imagen equirectangular opcional; el ruido solo le agrega detalle
+    albedo: Option<Texture>,
+}
+
+const SEA_LEVEL: f32 = 0.0;
+
+impl EarthShader {
+    pub fn new(seed: u64) -> Self {
+        let sand = Vector3::new(0.76, 0.7, 0.5);
+        EarthShader {
+            noise: Simplex::new(seed),
+            cities: Worley::new(seed.wrapping_add(1)),
+            palette: ColorRamp::new(Interpolation::Linear)
+                .with_stop(SEA_LEVEL - 0.33, Vector3::new(0.02, 0.08, 0.3))
+                .with_stop(SEA_LEVEL, Vector3::new(0.1, 0.4, 0.7))
+                .with_stop(SEA_LEVEL, sand)
+                .with_stop(SEA_LEVEL + 0.04, sand)
+                .with_stop(SEA_LEVEL + 0.04, Vector3::new(0.17, 0.44, 0.16))
+                .with_stop(SEA_LEVEL + 0.4, Vector3::new(0.45, 0.35, 0.25)),
+            albedo: None,
+        }
+    }
+
+    pub fn with_albedo(mut self, texture: Texture) -> Self {
+        self.albedo = Some(texture);
+        self
+    }
+
+    fn height(&self, q: Vector3) -> f32 {
+        Fbm::new(5).sample3(&self.noise, q.x * 2.0, q.y * 2.0, q.z * 2.0)
+    }
+
+    // Con imagen, es tierra lo que no es predominantemente azul
+    fn is_land(&self, dir: Vector3, q: Vector3) -> bool {
+        match &self.albedo {
+            Some(texture) => {
+                let color = sample_equirectangular(texture, dir);
+                color.z < color.x.max(color.y)
+            }
+            None => self.height(q) >= SEA_LEVEL + 0.04,
+        }
+    }
+
+    // La arena de la costa no es tierra firme, pero tampoco refleja como el agua
+    fn is_ocean(&self, dir: Vector3, q: Vector3) -> bool {
+        match &self.albedo {
+            Some(_) => !self.is_land(dir, q),
+            None => self.height(q) < SEA_LEVEL,
+        }
+    }
+
+    // Luces de ciudades: grupos de puntos sobre tierra firme que solo se
+    // encienden del lado nocturno y se apagan gradualmente en el terminador
+    fn city_lights(&self, dir: Vector3, q: Vector3, night: f32) -> Vector3 {
+        if !self.is_land(dir, q) || dir.y.abs() > 0.8 {
+            return Vector3::zero();
+        }
+
+        // Zonas pobladas según ruido de baja frecuencia, y dentro de ellas
+        // una ciudad por celda de Worley
+        let population = Fbm::new(3).sample3(&self.noise, q.x * 6.0 + 11.0, q.y * 6.0, q.z * 6.0);
+        let density = ((population - 0.05) * 4.0).clamp(0.0, 1.0);
+        let c = q * 25.0;
+        let f1 = self.cities.cells3(c.x, c.y, c.z).f1;
+        let city = (1.0 - f1 / 0.35).max(0.0).powi(3);
+
+        Vector3::new(1.0, 0.8, 0.45) * (city * density * night * 1.2)
+    }
+
+    // Reflejo especular del sol en el océano. Olas diminutas inclinan la
+    // normal con ruido animado, así el brillo se rompe en destellos que
+    // cambian mientras el planeta gira
+    fn glint(&self, fragment: &Fragment, uniforms: &Uniforms, q: Vector3) -> Vector3 {
+        let t = uniforms.time;
+        let w = q * 60.0;
+        let waves = Vector3::new(
+            self.noise.noise3(w.x + t * 0.8, w.y, w.z),
+            self.noise.noise3(w.x, w.y - t * 0.7, w.z + 17.0),
+            self.noise.noise3(w.x + 31.0, w.y, w.z + t * 0.9),
+        );
+        let normal = (fragment.normal + waves * 0.12).normalized();
+
+        let to_sun = (uniforms.nearest_light(fragment.world_position) - fragment.world_position)
+            .normalized();
+        let to_camera = (uniforms.camera_position - fragment.world_position).normalized();
+        let half = (to_sun + to_camera).normalized();
+        let specular = normal.dot(half).max(0.0).powf(180.0);
+
+        // Sin reflejo del lado nocturno ni sobre los casquetes de hielo
+        let day = (fragment.normal.dot(to_sun) / 0.1).clamp(0.0, 1.0);
+        let ice = match self.albedo {
+            Some(_) => 0.0,
+            None => polar_cap(fragment.object_position.normalized(), &self.noise, q, 0.38),
+        };
+        Vector3::new(1.0, 0.95, 0.85) * (specular * day * (1.0 - ice) * 1.5)
+    }
+}
+
+// Coordenadas equirectangulares: u da la vuelta, v va de polo a polo
+fn sample_equirectangular(texture: &Texture, dir: Vector3) -> Vector4 {
+    let uv = Vector2::new(
+        0.5 + dir.z.atan2(dir.x) / (2.0 * PI),
+        0.5 + dir.y.clamp(-1.0, 1.0).asin() / PI,
+    );
+    let sampler = Sampler {
+        wrap_v: Wrap::Clamp,
+        ..Sampler::default()
+    };
+    texture.sample(&sampler, uv)
+}
+
+impl FragmentShader for EarthShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector4 {
+        // Dirección desde el centro de la esfera: fija a la superficie del modelo
+        let dir = fragment.object_position.normalized();
+        let q = dir + seed_offset(uniforms.seed);
+
+        if let Some(albedo) = &self.albedo {
+            let color = sample_equirectangular(albedo, dir);
+            let detail = Fbm::new(3).sample3(&self.noise, q.x * 16.0, q.y * 16.0, q.z * 16.0);
+            let shade = 1.0 + 0.15 * detail;
+            return Vector4::new(color.x * shade, color.y * shade, color.z * shade, 1.0);
+        }
+
+        let color = self.palette.sample(self.height(q));
+
+        let cap = polar_cap(dir, &self.noise, q, 0.38);
+        let color = color.lerp(Vector3::new(0.95, 0.97, 1.0), cap);
+
+        Vector4::new(color.x, color.y, color.z, 1.0)
+    }
+
+    // Lo que no depende del difuso: luces de ciudades de noche y el reflejo
+    // del sol sobre el agua de día
+    fn emission(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
+        let dir = fragment.object_position.normalized();
+        let q = dir + seed_offset(uniforms.seed);
+        let to_sun = (uniforms.nearest_light(fragment.world_position) - fragment.world_position)
+            .normalized();
+        let night = ((0.05 - fragment.normal.dot(to_sun)) / 0.2).clamp(0.0, 1.0);
+
+        if night > 0.0 {
+            self.city_lights(dir, q, night)
+        } else if self.is_ocean(dir, q) {
+            self.glint(fragment, uniforms, q)
+        } else {
+            Vector3::zero()
+        }
+    }
+}
+
+// Reflejo del sol en el hielo: concentrado y fuerte
+const ICE_SHININESS: f32 = 60.0;
+const ICE_SPECULAR: f32 = 0.8;
+
+// 🧊 Planeta helado con grietas y tinte azulado bajo la superficie
+pub struct IceShader {
+    noise: Simplex,
+    cracks: Worley,
+}
+
+impl IceShader {
+    pub fn new(seed: u64) -> Self {
+        IceShader {
+            noise: Simplex::new(seed),
+            cracks: Worley::new(seed.wrapping_add(1)),
+        }
+    }
+
+    // Las grietas siguen los bordes entre celdas (F2 - F1 cercano a cero),
+    // en dos escalas para que no se vea una rejilla regular
+    fn crack(&self, dir: Vector3) -> f32 {
+        let large = self.cracks.cells3(dir.x * 4.0, dir.y * 4.0, dir.z * 4.0);
+        let small = self.cracks.cells3(dir.x * 11.0, dir.y * 11.0, dir.z * 11.0);
+        let crack = (1.0 - (large.f2 - large.f1) * 12.0).max(0.0)
+            + (1.0 - (small.f2 - small.f1) * 20.0).max(0.0) * 0.5;
+        crack.min(1.0)
+    }
+}
+
+impl FragmentShader for IceShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector4 {
+        let dir = fragment.object_position.normalized() + seed_offset(uniforms.seed);
+        let crack = self.crack(dir);
+
+        // Tinte azul que varía suavemente, como hielo con distinto espesor
+        let depth =
+            Fbm::new(3).sample3(&self.noise, dir.x * 3.0, dir.y * 3.0, dir.z * 3.0) * 0.5 + 0.5;
+        let surface = Vector3::new(0.85, 0.93, 1.0).lerp(Vector3::new(0.55, 0.75, 0.95), depth);
+
+        // Dentro de las grietas se ve el azul profundo del hielo
+        let color = surface.lerp(Vector3::new(0.15, 0.35, 0.6), crack * 0.8);
+        Vector4::new(color.x, color.y, color.z, 1.0)
+    }
+
+    // El hielo liso refleja el sol casi como un espejo; las grietas, ásperas,
+    // no brillan
+    fn emission(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
+        let dir = fragment.object_position.normalized() + seed_offset(uniforms.seed);
+        let highlight = specular(
+            self.normal(fragment, uniforms),
+            fragment.world_position,
+            uniforms.camera_position,
+            uniforms.lights,
+            uniforms.occluders,
+            ICE_SHININESS,
+        );
+        highlight * ICE_SPECULAR * (1.0 - self.crack(dir))
+    }
+}
+
+// 🌋 Planeta de lava: corteza de basalto con grietas de magma que brillan
+pub struct LavaShader {
+    noise: Simplex,
+    cracks: Worley,
+    // Cuántas celdas de corteza hay: más densidad, más grietas
+    crack_density: f32,
+    glow_color: Vector3,
+}
+
+impl LavaShader {
+    pub fn new(seed: u64, crack_density: f32, glow_color: Vector3) -> Self {
+        LavaShader {
+            noise: Simplex::new(seed),
+            cracks: Worley::new(seed.wrapping_add(1)),
+            crack_density,
+            glow_color,
+        }
+    }
+}
+
+impl FragmentShader for LavaShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector4 {
+        let dir = fragment.object_position.normalized() + seed_offset(uniforms.seed);
+        let t = uniforms.time;
+
+        // Corteza oscura con algo de variación
+        let rock =
+            Fbm::new(4).sample3(&self.noise, dir.x * 5.0, dir.y * 5.0, dir.z * 5.0) * 0.5 + 0.5;
+        let crust = Vector3::new(0.08, 0.06, 0.05).lerp(Vector3::new(0.22, 0.18, 0.16), rock);
+
+        // Grietas en los bordes de las placas; el ruido las ensancha y
+        // angosta con el tiempo para que el magma parezca fluir
+        let d = self.crack_density;
+        let cells = self.cracks.cells3(dir.x * d, dir.y * d, dir.z * d);
+        let flow = self
+            .noise
+            .noise3(dir.x * 3.0 + t * 0.3, dir.y * 3.0, dir.z * 3.0 - t * 0.2)
+            * 0.5
+            + 0.5;
+        let width = 0.06 + 0.08 * flow;
+        let crack = (1.0 - (cells.f2 - cells.f1) / width).clamp(0.0, 1.0);
+
+        // Pulso del brillo; puede pasar de 1 porque es emisivo
+        let pulse = 1.0 + 0.3 * (t * 2.0 + flow * 6.0).sin();
+        let glow = self.glow_color * (crack * crack * pulse * 1.5);
+
+        let color = crust * (1.0 - crack) + glow;
+        Vector4::new(color.x, color.y, color.z, 1.0)
+    }
+}
+
+// ☁️ Capa de nubes para una esfera un poco más grande que el planeta
+pub struct CloudShader {
+    noise: Simplex,
+    // Fracción aproximada del cielo cubierta, entre 0 y 1
+    coverage: f32,
+}
+
+impl CloudShader {
+    pub fn new(seed: u64, coverage: f32) -> Self {
+        CloudShader {
+            noise: Simplex::new(seed),
+            coverage,
+        }
+    }
+}
+
+impl FragmentShader for CloudShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector4 {
+        // Las nubes giran alrededor del eje Y a su propio ritmo, sin depender
+        // de la superficie que tapan
+        let angle = uniforms.time * 0.05;
+        let (sin, cos) = angle.sin_cos();
+        let d = fragment.object_position.normalized();
+        let dir = Vector3::new(d.x * cos - d.z * sin, d.y, d.x * sin + d.z * cos)
+            + seed_offset(uniforms.seed);
+
+        let density =
+            Fbm::new(5).sample3(&self.noise, dir.x * 3.0, dir.y * 6.0, dir.z * 3.0) * 0.5 + 0.5;
+        // Con más cobertura baja el umbral a partir del cual hay nube
+        let threshold = 1.0 - self.coverage;
+        let alpha = ((density - threshold) / 0.15).clamp(0.0, 1.0) * 0.85;
+
+        Vector4::new(1.0, 1.0, 1.0, alpha)
+    }
+}
+
+// 🌫️ Brillo atmosférico en el borde (Fresnel) para una capa exterior
+pub struct AtmosphereShader {
+    color: Vector3,
+    // Más alto, más fino el halo
+    power: f32,
+    aurora: Option<Aurora>,
+}
+
+impl AtmosphereShader {
+    pub fn new(color: Vector3, power: f32) -> Self {
+        AtmosphereShader {
+            color,
+            power,
+            aurora: None,
+        }
+    }
+
+    pub fn with_aurora(mut self, aurora: Aurora) -> Self {
+        self.aurora = Some(aurora);
+        self
+    }
+
+    fn aurora_intensity(&self, fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+        let Some(aurora) = &self.aurora else {
+            return 0.0;
+        };
+        // Casi invisible de día
+        let to_sun = (uniforms.nearest_light(fragment.world_position) - fragment.world_position)
+            .normalized();
+        let night = ((0.1 - fragment.normal.dot(to_sun)) / 0.3).clamp(0.0, 1.0);
+        let dir = fragment.object_position.normalized() + seed_offset(uniforms.seed);
+        aurora.intensity(dir.normalized(), uniforms.time) * (0.15 + 0.85 * night)
+    }
+}
+
+// 🌠 Aurora: cortinas de luz en un óvalo alrededor de cada polo magnético,
+// un poco inclinado respecto al eje de giro
+pub struct Aurora {
+    noise: Simplex,
+    // Distancia angular del óvalo al polo, en radianes
+    pub radius: f32,
+    pub low_color: Vector3,
+    pub high_color: Vector3,
+}
+
+impl Aurora {
+    pub fn new(seed: u64, radius: f32) -> Self {
+        Aurora {
+            noise: Simplex::new(seed),
+            radius,
+            low_color: Vector3::new(0.2, 1.0, 0.45),
+            high_color: Vector3::new(0.65, 0.25, 0.9),
+        }
+    }
+
+    // Posición dentro de la franja: 0 en el borde hacia el ecuador y 1 en el
+    // borde hacia el polo; `None` fuera de ella
+    fn band(&self, dir: Vector3, time: f32) -> Option<(f32, f32)> {
+        let axis = Vector3::new(0.15, 1.0, 0.1).normalized();
+        let from_pole = dir.dot(axis).abs().clamp(0.0, 1.0).acos();
+        let longitude = dir.z.atan2(dir.x);
+
+        // El óvalo ondula con el ruido y el tiempo
+        let wobble = self.noise.noise2(longitude * 1.5, time * 0.1) * 0.06;
+        let width = 0.12;
+        let offset = (self.radius + wobble - from_pole) / width + 0.5;
+        (0.0..=1.0).contains(&offset).then_some((offset, longitude))
+    }
+
+    pub fn intensity(&self, dir: Vector3, time: f32) -> f32 {
+        let Some((offset, longitude)) = self.band(dir, time) else {
+            return 0.0;
+        };
+        // Rayos verticales finos que se desplazan a lo largo del óvalo
+        let rays = self.noise.noise2(longitude * 24.0 + time * 0.4, time * 0.2) * 0.5 + 0.5;
+        let curtain = self.noise.noise2(longitude * 5.0 - time * 0.15, 7.0) * 0.5 + 0.5;
+        // Borde inferior nítido y difuminado hacia arriba
+        let profile = (offset / 0.15).min(1.0) * (1.0 - offset);
+        profile * (rays * rays * 0.7 + curtain * 0.3) * 2.0
+    }
+
+    pub fn color(&self, dir: Vector3, time: f32) -> Vector3 {
+        match self.band(dir, time) {
+            Some((offset, _)) => self.low_color.lerp(self.high_color, offset),
+            None => Vector3::zero(),
+        }
+    }
+}
+
+impl FragmentShader for AtmosphereShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector4 {
+        let to_camera = (uniforms.camera_position - fragment.world_position).normalized();
+        // De frente n·v ≈ 1 y no se ve nada; en la silueta n·v ≈ 0 y brilla
+        let facing = fragment.normal.dot(to_camera).clamp(0.0, 1.0);
+        let rim = (1.0 - facing).powf(self.power);
+        // La aurora necesita cubrir también el centro del disco
+        let alpha = rim.max(self.aurora_intensity(fragment, uniforms).min(1.0));
+        Vector4::new(self.color.x, self.color.y, self.color.z, alpha)
+    }
+
+    fn emission(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
+        let Some(aurora) = &self.aurora else {
+            return Vector3::zero();
+        };
+        let dir = fragment.object_position.normalized() + seed_offset(uniforms.seed);
+        aurora.color(dir.normalized(), uniforms.time) * self.aurora_intensity(fragment, uniforms)
+    }
+}
+
+// 🪐 Anillos planetarios: franjas de densidad a lo largo del radio
+pub struct RingShader {
+    noise: Simplex,
+    color: Vector3,
+}
+
+impl RingShader {
+    pub fn new(seed: u64, color: Vector3) -> Self {
+        RingShader {
+            noise: Simplex::new(seed),
+            color,
+        }
+    }
+}
+
+impl FragmentShader for RingShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector4 {
+        // u del anillo: 0 en el borde interior y 1 en el exterior
+        let r = fragment.tex_coords.x;
+        let offset = seed_offset(uniforms.seed);
+
+        // Muchas franjas finas más unas pocas anchas, con huecos donde la
+        // densidad baja del umbral (como la división de Cassini)
+        let fine = self.noise.noise2(r * 60.0 + offset.x, 0.5) * 0.5 + 0.5;
+        let broad = self.noise.noise2(r * 8.0 + offset.y, 3.5) * 0.5 + 0.5;
+        let density = (fine * 0.4 + broad * 0.6 - 0.2).clamp(0.0, 1.0);
+
+        // Se desvanece suavemente en ambos bordes
+        let edges = (r / 0.08).min((1.0 - r) / 0.15).clamp(0.0, 1.0);
+        let alpha = density * edges * 0.9;
+
+        let color = self.color * (0.7 + 0.3 * fine);
+        Vector4::new(color.x, color.y, color.z, alpha)
+    }
+}
+
+// 🌑 Luna gris con cráteres de varios tamaños
+const MOON_RELIEF: f32 = 0.04;
+
+pub struct MoonShader {
+    noise: Simplex,
+    craters: Worley,
+}
+
+impl MoonShader {
+    pub fn new(seed: u64) -> Self {
+        MoonShader {
+            noise: Simplex::new(seed),
+            craters: Worley::new(seed.wrapping_add(1)),
+        }
+    }
+
+    // Un cráter por celda: fondo oscuro y borde claro alrededor del punto
+    fn crater(&self, dir: Vector3, frequency: f32, radius: f32) -> f32 {
+        let p = dir * frequency;
+        let f1 = self.craters.cells3(p.x, p.y, p.z).f1;
+        if f1 < radius {
+            // Más hondo hacia el centro
+            0.7 + 0.3 * (f1 / radius)
+        } else if f1 < radius * 1.25 {
+            1.2
+        } else {
+            1.0
+        }
+    }
+
+    // Relieve de los mismos cráteres: cuenco hundido con el borde levantado,
+    // continuo para que las diferencias finitas no den saltos. Los chicos
+    // son menos hondos.
+    fn relief(&self, dir: Vector3) -> f32 {
+        [
+            (3.0, 0.2, Vector3::zero()),
+            (8.0, 0.25, Vector3::new(7.1, 2.3, 5.9)),
+            (20.0, 0.3, Vector3::new(3.7, 9.4, 1.2)),
+        ]
+        .iter()
+        .map(|&(frequency, radius, shift)| {
+            let p = (dir + shift) * frequency;
+            let t = self.craters.cells3(p.x, p.y, p.z).f1 / radius;
+            let depth = if t < 1.0 {
+                t * t - 0.8
+            } else if t < 1.5 {
+                0.2 * (3.0 - 2.0 * t).powi(2)
+            } else {
+                0.0
+            };
+            depth / frequency
+        })
+        .sum()
+    }
+}
+
+impl FragmentShader for MoonShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector4 {
+        let dir = fragment.object_position.normalized() + seed_offset(uniforms.seed);
+
+        // Mares oscuros y tierras altas claras
+        let maria =
+            Fbm::new(4).sample3(&self.noise, dir.x * 2.0, dir.y * 2.0, dir.z * 2.0) * 0.5 + 0.5;
+        let base = 0.35 + 0.3 * maria;
+
+        // Cráteres grandes y escasos encima de muchos chicos
+        let craters = self.crater(dir, 3.0, 0.2)
+            * self.crater(dir + Vector3::new(7.1, 2.3, 5.9), 8.0, 0.25)
+            * self.crater(dir + Vector3::new(3.7, 9.4, 1.2), 20.0, 0.3);
+
+        let gray = base * craters;
+        Vector4::new(gray, gray * 0.98, gray * 0.95, 1.0)
+    }
+
+    fn normal(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
+        let offset = seed_offset(uniforms.seed);
+        let bumped = perturbed_normal(fragment.object_position, MOON_RELIEF, |d| {
+            self.relief(d + offset)
+        });
+        to_world(&uniforms.model_matrix, bumped)
+    }
+}
+
+// 📜 Shader definido en un archivo de texto (ver `script.rs`); se vuelve a
+// leer cuando el archivo cambia, sin recompilar
+pub struct ScriptShader {
+    path: String,
+    program: Program,
+    modified: Option<SystemTime>,
+    noise: ScriptNoise,
+}
+
+// Lo que se dibuja mientras el archivo no se puede leer o tiene errores
+const PLACEHOLDER_SCRIPT: &str = "r = 1\ng = 0\nb = 1";
+
+impl ScriptShader {
+    // Con un error se avisa por consola y se dibuja en magenta; el archivo se
+    // sigue vigilando, así que basta con corregirlo y guardar
+    pub fn load(path: &str, seed: u64) -> Self {
+        let program = read_program(path).unwrap_or_else(|message| {
+            eprintln!("❌ {path}: {message}");
+            Program::parse(PLACEHOLDER_SCRIPT).expect("el shader de reemplazo es válido")
+        });
+        ScriptShader {
+            path: path.to_string(),
+            program,
+            modified: modified_time(path),
+            noise: ScriptNoise {
+                simplex: Simplex::new(seed),
+                cells: Worley::new(seed.wrapping_add(1)),
+            },
+        }
+    }
+
+    // Si el archivo cambió se vuelve a parsear; con un error se avisa por
+    // consola y se sigue usando la versión anterior
+    pub fn reload_if_changed(&mut self) {
+        let modified = modified_time(&self.path);
+        if modified == self.modified {
+            return;
+        }
+        self.modified = modified;
+
+        match read_program(&self.path) {
+            Ok(program) => {
+                self.program = program;
+                println!("🔄 Shader recargado: {}", self.path);
+            }
+            Err(message) => eprintln!("❌ {}: {message}", self.path),
+        }
+    }
+}
+
+fn read_program(path: &str) -> Result<Program, String> {
+    fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|source| Program::parse(&source))
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl FragmentShader for ScriptShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Vector4 {
+        let dir = fragment.object_position.normalized() + seed_offset(uniforms.seed);
+        let inputs = [
+            dir.x,
+            dir.y,
+            dir.z,
+            fragment.tex_coords.x,
+            fragment.tex_coords.y,
+            uniforms.time,
+        ];
+        let [r, g, b, a] = self.program.run(inputs, &self.noise);
+        Vector4::new(r, g, b, a)
+    }
+}
+
+// Color plano: el contorno del cuerpo seleccionado (sin iluminar) y los
+// cuerpos con un shader desconocido
+pub struct SolidShader {
+    pub color: Vector3,
+}
+
+impl FragmentShader for SolidShader {
+    fn shade(&self, _fragment: &Fragment, _uniforms: &Uniforms) -> Vector4 {
+        Vector4::new(self.color.x, self.color.y, self.color.z, 1.0)
+    }
+}
+
+// ☄️ Partículas de la cola de un cometa: el color viene del vértice y el alfa
+// en la coordenada u (ver `ParticleSystem::mesh`)
+pub struct TailShader;
+
+impl FragmentShader for TailShader {
+    fn emissive(&self) -> bool {
+        true
+    }
+
+    fn shade(&self, fragment: &Fragment, _uniforms: &Uniforms) -> Vector4 {
+        let c = fragment.color;
+        Vector4::new(c.x, c.y, c.z, fragment.tex_coords.x * 0.6)
+    }
+}
+
+// 🌌 Fondo de estrellas: una esfera enorme alrededor de la cámara que
+// muestrea un cubemap con la dirección de cada fragmento
+pub struct SkyboxShader {
+    cubemap: Cubemap,
+}
+
+impl SkyboxShader {
+    pub fn new(cubemap: Cubemap) -> Self {
+        SkyboxShader { cubemap }
+    }
+
+    // Cubemap procedural: estrellas puntuales de distinto brillo sobre una
+    // nebulosa tenue
+    pub fn star_field(seed: u64, size: i32) -> Self {
+        let stars = Worley::new(seed);
+        let nebula = Simplex::new(seed.wrapping_add(1));
+        let cubemap = Cubemap::from_fn(size, |dir| {
+            let p = dir * 60.0;
+            let cells = stars.cells3(p.x, p.y, p.z);
+            let star = (1.0 - cells.f1 / 0.06).max(0.0).powi(2) * (cells.f2 * 2.0).min(1.0);
+            let haze = (Fbm::new(4).sample3(&nebula, dir.x * 2.0, dir.y * 2.0, dir.z * 2.0) * 0.5
+                + 0.5)
+                .powi(3)
+                * 0.12;
+            Vector4::new(star + haze * 0.6, star + haze * 0.3, star + haze, 1.0)
+        });
+        SkyboxShader { cubemap }
+    }
+}
+
+impl FragmentShader for SkyboxShader {
+    fn emissive(&self) -> bool {
+        true
+    }
+
+    fn shade(&self, fragment: &Fragment, _uniforms: &Uniforms) -> Vector4 {
+        self.cubemap.sample(fragment.object_position.normalized())
+    }
+}
+
+// Una capa de nebulosa: un fBm deformado de un solo color que solo aparece
+// donde supera `threshold`
+struct NebulaLayer {
+    color: Vector3,
+    scale: f32,
+    threshold: f32,
+    offset: Vector3,
+}
+
+// ☁️ Nebulosas de fondo: varias capas de fBm superpuestas, más densas a lo
+// largo de una franja del cielo (como la Vía Láctea). Se hornean en un
+// cubemap y se suman encima del skybox.
+pub struct NebulaShader {
+    cubemap: Cubemap,
+}
+
+impl NebulaShader {
+    pub fn new(seed: u64, size: i32) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let noise = Simplex::new(seed);
+        let warp = DomainWarp::new(3, 0.6);
+        let palette = [
+            Vector3::new(0.9, 0.25, 0.5),
+            Vector3::new(0.25, 0.45, 1.0),
+            Vector3::new(0.3, 0.9, 0.8),
+            Vector3::new(1.0, 0.55, 0.2),
+        ];
+        let layers: Vec<NebulaLayer> = (0..3)
+            .map(|_| NebulaLayer {
+                color: palette[rng.gen_range(0..palette.len())],
+                scale: rng.gen_range(1.5..3.5),
+                threshold: rng.gen_range(0.5..0.6),
+                offset: random_direction(&mut rng) * 40.0,
+            })
+            .collect();
+        let pole = random_direction(&mut rng);
+
+        let cubemap = Cubemap::from_fn(size, |dir| {
+            let band = 0.15 + 0.85 * (-dir.dot(pole).powi(2) / 0.08).exp();
+            let color = layers.iter().fold(Vector3::zero(), |color, layer| {
+                let p = warp.warp3(&noise, dir * layer.scale + layer.offset);
+                let n = Fbm::new(5).sample3(&noise, p.x, p.y, p.z) * 0.5 + 0.5;
+                let density = ((n - layer.threshold) / (1.0 - layer.threshold)).clamp(0.0, 1.0);
+                color + layer.color * density.powf(1.5)
+            }) * (band * 0.5);
+            Vector4::new(color.x, color.y, color.z, 1.0)
+        });
+        NebulaShader { cubemap }
+    }
+}
+
+impl FragmentShader for NebulaShader {
+    fn emissive(&self) -> bool {
+        true
+    }
+
+    fn shade(&self, fragment: &Fragment, _uniforms: &Uniforms) -> Vector4 {
+        self.cubemap.sample(fragment.object_position.normalized())
+    }
+}
+
+// Una galaxia lejana: un sprite cuadrado en el cielo, perpendicular a su
+// dirección, con un disco en espiral (o una elíptica sin brazos)
+struct Galaxy {
+    direction: Vector3,
+    right: Vector3,
+    up: Vector3,
+    // Medio ancho del sprite, como tangente del ángulo que ocupa
+    size: f32,
+    // Coseno de la inclinación del disco: 1 = de frente, cerca de 0 = de canto
+    tilt: f32,
+    // 0 = elíptica
+    arms: u32,
+    twist: f32,
+    color: Vector3,
+}
+
+impl Galaxy {
+    fn glow(&self, dir: Vector3) -> Vector3 {
+        let facing = dir.dot(self.direction);
+        if facing < 0.9 {
+            return Vector3::zero();
+        }
+        // Intersección con el plano del sprite, en [-1, 1] dentro del cuadrado
+        let p = dir / facing;
+        let x = p.dot(self.right) / self.size;
+        let y = p.dot(self.up) / self.size;
+        if x.abs() > 1.0 || y.abs() > 1.0 {
+            return Vector3::zero();
+        }
+
+        // Se deshace la inclinación para medir sobre el disco
+        let y = y / self.tilt;
+        let r = (x * x + y * y).sqrt();
+        let arms = if self.arms == 0 {
+            1.0
+        } else {
+            let phase = self.arms as f32 * (y.atan2(x) - self.twist * r.max(1e-3).ln());
+            (0.5 + 0.5 * phase.cos()).powi(3)
+        };
+        let core = (-r * r / 0.01).exp();
+        let disk = (-r * 3.5).exp() * (0.25 + 0.75 * arms);
+        let edge = (1.0 - r * r).max(0.0);
+        // Estrellas viejas y amarillas en el núcleo, jóvenes en los brazos
+        let tint = Vector3::new(1.0, 0.85, 0.6).lerp(self.color, (r * 3.0).min(1.0));
+        tint * ((core * 0.6 + disk * 0.9) * edge)
+    }
+}
+
+// 🌀 Galaxias lejanas sobre el fondo, evaluadas en la misma esfera del
+// skybox: como esa esfera sigue a la cámara, quedan a distancia infinita
+pub struct GalaxyShader {
+    galaxies: Vec<Galaxy>,
+}
+
+impl GalaxyShader {
+    pub fn new(seed: u64, count: usize) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let galaxies = (0..count)
+            .map(|_| {
+                let direction = random_direction(&mut rng);
+                let helper = if direction.y.abs() < 0.9 {
+                    Vector3::new(0.0, 1.0, 0.0)
+                } else {
+                    Vector3::new(1.0, 0.0, 0.0)
+                };
+                let right = helper.cross(direction).normalized();
+                let up = direction.cross(right);
+                // Cada una girada a su manera dentro del sprite
+                let (sin, cos) = rng.gen_range(0.0..2.0 * PI).sin_cos();
+                Galaxy {
+                    direction,
+                    right: right * cos + up * sin,
+                    up: up * cos - right * sin,
+                    size: rng.gen_range(0.015..0.04),
+                    tilt: rng.gen_range(0.25..1.0),
+                    arms: if rng.gen_bool(0.25) {
+                        0
+                    } else {
+                        rng.gen_range(2..=3)
+                    },
+                    twist: rng.gen_range(2.5..4.5),
+                    color: Vector3::new(0.7, 0.8, 1.0)
+                        .lerp(Vector3::new(1.0, 0.75, 0.85), rng.gen_range(0.0..1.0)),
+                }
+            })
+            .collect();
+        GalaxyShader { galaxies }
+    }
+}
+
+impl FragmentShader for GalaxyShader {
+    fn emissive(&self) -> bool {
+        true
+    }
+
+    fn shade(&self, fragment: &Fragment, _uniforms: &Uniforms) -> Vector4 {
+        let dir = fragment.object_position.normalized();
+        let color = self
+            .galaxies
+            .iter()
+            .fold(Vector3::zero(), |color, galaxy| color + galaxy.glow(dir));
+        Vector4::new(color.x, color.y, color.z, 1.0)
+    }
+}
+
+// Dirección uniforme sobre la esfera
+fn random_direction(rng: &mut StdRng) -> Vector3 {
+    let z: f32 = rng.gen_range(-1.0..1.0);
+    let angle = rng.gen_range(0.0..2.0 * PI);
+    let ring = (1.0 - z * z).sqrt();
+    Vector3::new(ring * angle.cos(), ring * angle.sin(), z)
 }
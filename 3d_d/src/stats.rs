@@ -0,0 +1,34 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Contadores de depuración de lo que el pipeline descarta en cada frame
+static DEGENERATE_TRIANGLES: AtomicUsize = AtomicUsize::new(0);
+static NON_FINITE_TRIANGLES: AtomicUsize = AtomicUsize::new(0);
+static NON_FINITE_FRAGMENTS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameStats {
+    pub degenerate_triangles: usize,
+    pub non_finite_triangles: usize,
+    pub non_finite_fragments: usize,
+}
+
+pub fn count_degenerate_triangle() {
+    DEGENERATE_TRIANGLES.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn count_non_finite_triangle() {
+    NON_FINITE_TRIANGLES.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn count_non_finite_fragment() {
+    NON_FINITE_FRAGMENTS.fetch_add(1, Ordering::Relaxed);
+}
+
+// Devuelve los contadores acumulados desde la última llamada y los reinicia
+pub fn take_frame_stats() -> FrameStats {
+    FrameStats {
+        degenerate_triangles: DEGENERATE_TRIANGLES.swap(0, Ordering::Relaxed),
+        non_finite_triangles: NON_FINITE_TRIANGLES.swap(0, Ordering::Relaxed),
+        non_finite_fragments: NON_FINITE_FRAGMENTS.swap(0, Ordering::Relaxed),
+    }
+}
@@ -0,0 +1,188 @@
+use raylib::prelude::*;
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+// Qué pasa con las coordenadas fuera de [0, 1]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Wrap {
+    Repeat,
+    Clamp,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Sampler {
+    pub filter: Filter,
+    pub wrap_u: Wrap,
+    pub wrap_v: Wrap,
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Sampler {
+            filter: Filter::Bilinear,
+            wrap_u: Wrap::Repeat,
+            wrap_v: Wrap::Repeat,
+        }
+    }
+}
+
+// Imagen en memoria con colores RGBA en [0, 1], lista para muestrear desde
+// un fragment shader
+pub struct Texture {
+    width: i32,
+    height: i32,
+    pixels: Vec<Vector4>,
+}
+
+impl Texture {
+    pub fn new(width: i32, height: i32, pixels: Vec<Vector4>) -> Self {
+        assert_eq!(pixels.len(), (width * height) as usize);
+        Texture {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    // Acepta los formatos que carga raylib (PNG, JPEG, ...)
+    pub fn load(path: &str) -> Result<Self, String> {
+        let image = Image::load_image(path).map_err(|e| format!("{path}: {e}"))?;
+        let pixels = image
+            .get_image_data()
+            .iter()
+            .map(|c| {
+                Vector4::new(
+                    c.r as f32 / 255.0,
+                    c.g as f32 / 255.0,
+                    c.b as f32 / 255.0,
+                    c.a as f32 / 255.0,
+                )
+            })
+            .collect();
+        Ok(Texture {
+            width: image.width(),
+            height: image.height(),
+            pixels,
+        })
+    }
+
+    // v = 0 es el borde inferior de la imagen, como en los OBJ
+    pub fn sample(&self, sampler: &Sampler, uv: Vector2) -> Vector4 {
+        let x = uv.x * self.width as f32 - 0.5;
+        let y = (1.0 - uv.y) * self.height as f32 - 0.5;
+
+        match sampler.filter {
+            Filter::Nearest => self.texel(sampler, x.round() as i32, y.round() as i32),
+            Filter::Bilinear => {
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i32, y0 as i32);
+                let top = self
+                    .texel(sampler, x0, y0)
+                    .lerp(self.texel(sampler, x0 + 1, y0), tx);
+                let bottom = self
+                    .texel(sampler, x0, y0 + 1)
+                    .lerp(self.texel(sampler, x0 + 1, y0 + 1), tx);
+                top.lerp(bottom, ty)
+            }
+        }
+    }
+
+    fn texel(&self, sampler: &Sampler, x: i32, y: i32) -> Vector4 {
+        let x = wrap(x, self.width, sampler.wrap_u);
+        let y = wrap(y, self.height, sampler.wrap_v);
+        self.pixels[(y * self.width + x) as usize]
+    }
+}
+
+fn wrap(index: i32, size: i32, mode: Wrap) -> i32 {
+    match mode {
+        Wrap::Repeat => index.rem_euclid(size),
+        Wrap::Clamp => index.clamp(0, size - 1),
+    }
+}
+
+// Seis caras cuadradas en el orden +X, -X, +Y, -Y, +Z, -Z, muestreadas con
+// una dirección (convención de OpenGL para ubicar cada cara)
+pub struct Cubemap {
+    faces: [Texture; 6],
+}
+
+impl Cubemap {
+    pub fn load(paths: [&str; 6]) -> Result<Self, String> {
+        let [px, nx, py, ny, pz, nz] = paths.map(Texture::load);
+        Ok(Cubemap {
+            faces: [px?, nx?, py?, ny?, pz?, nz?],
+        })
+    }
+
+    // Llena cada texel con el color que `color` devuelve para su dirección
+    pub fn from_fn(size: i32, color: impl Fn(Vector3) -> Vector4) -> Self {
+        let face = |index: usize| {
+            let mut pixels = Vec::with_capacity((size * size) as usize);
+            for y in 0..size {
+                for x in 0..size {
+                    let u = (x as f32 + 0.5) / size as f32;
+                    let v = 1.0 - (y as f32 + 0.5) / size as f32;
+                    pixels.push(color(face_direction(index, u, v)));
+                }
+            }
+            Texture::new(size, size, pixels)
+        };
+        Cubemap {
+            faces: [face(0), face(1), face(2), face(3), face(4), face(5)],
+        }
+    }
+
+    pub fn sample(&self, direction: Vector3) -> Vector4 {
+        let (index, u, v) = face_uv(direction);
+        let sampler = Sampler {
+            filter: Filter::Bilinear,
+            wrap_u: Wrap::Clamp,
+            wrap_v: Wrap::Clamp,
+        };
+        self.faces[index].sample(&sampler, Vector2::new(u, v))
+    }
+}
+
+// Cara según el eje dominante, y coordenadas (s, t) en [-1, 1] dentro de ella
+fn face_uv(d: Vector3) -> (usize, f32, f32) {
+    let (ax, ay, az) = (d.x.abs(), d.y.abs(), d.z.abs());
+    let (index, s, t, major) = if ax >= ay && ax >= az {
+        if d.x > 0.0 {
+            (0, -d.z, -d.y, ax)
+        } else {
+            (1, d.z, -d.y, ax)
+        }
+    } else if ay >= az {
+        if d.y > 0.0 {
+            (2, d.x, d.z, ay)
+        } else {
+            (3, d.x, -d.z, ay)
+        }
+    } else if d.z > 0.0 {
+        (4, d.x, -d.y, az)
+    } else {
+        (5, -d.x, -d.y, az)
+    };
+    (index, (s / major + 1.0) * 0.5, (t / major + 1.0) * 0.5)
+}
+
+// Inversa de `face_uv`
+fn face_direction(index: usize, u: f32, v: f32) -> Vector3 {
+    let (s, t) = (u * 2.0 - 1.0, v * 2.0 - 1.0);
+    let direction = match index {
+        0 => Vector3::new(1.0, -t, -s),
+        1 => Vector3::new(-1.0, -t, s),
+        2 => Vector3::new(s, 1.0, t),
+        3 => Vector3::new(s, -1.0, -t),
+        4 => Vector3::new(s, -t, 1.0),
+        _ => Vector3::new(-s, -t, -1.0),
+    };
+    direction.normalized()
+}
@@ -0,0 +1,67 @@
+use crate::triangle::ScreenRect;
+use crate::vertex_output::VertexOutput;
+
+pub const TILE_SIZE: i32 = 32;
+
+pub struct Tile {
+    pub rect: ScreenRect,
+    // Índices de los triángulos que tocan este tile
+    pub triangles: Vec<usize>,
+}
+
+// Reparte triángulos ya proyectados en tiles de pantalla para rasterizar
+// cada tile por separado
+pub struct TileBinner {
+    tiles_x: i32,
+    tiles_y: i32,
+    pub tiles: Vec<Tile>,
+}
+
+impl TileBinner {
+    pub fn new(width: u32, height: u32) -> Self {
+        let tiles_x = (width as i32 + TILE_SIZE - 1) / TILE_SIZE;
+        let tiles_y = (height as i32 + TILE_SIZE - 1) / TILE_SIZE;
+
+        let mut tiles = Vec::with_capacity((tiles_x * tiles_y) as usize);
+        for ty in 0..tiles_y {
+            for tx in 0..tiles_x {
+                let min_x = tx * TILE_SIZE;
+                let min_y = ty * TILE_SIZE;
+                let max_x = (min_x + TILE_SIZE).min(width as i32) - 1;
+                let max_y = (min_y + TILE_SIZE).min(height as i32) - 1;
+                tiles.push(Tile {
+                    rect: ScreenRect::new(min_x, min_y, max_x, max_y),
+                    triangles: Vec::new(),
+                });
+            }
+        }
+
+        TileBinner {
+            tiles_x,
+            tiles_y,
+            tiles,
+        }
+    }
+
+    pub fn bin(&mut self, index: usize, triangle: &[VertexOutput; 3]) {
+        let xs = triangle.iter().map(|v| v.screen_position.x);
+        let ys = triangle.iter().map(|v| v.screen_position.y);
+        let min_x = xs.clone().fold(f32::INFINITY, f32::min).floor() as i32;
+        let max_x = xs.fold(f32::NEG_INFINITY, f32::max).ceil() as i32;
+        let min_y = ys.clone().fold(f32::INFINITY, f32::min).floor() as i32;
+        let max_y = ys.fold(f32::NEG_INFINITY, f32::max).ceil() as i32;
+
+        let first_tx = (min_x / TILE_SIZE).max(0);
+        let last_tx = (max_x / TILE_SIZE).min(self.tiles_x - 1);
+        let first_ty = (min_y / TILE_SIZE).max(0);
+        let last_ty = (max_y / TILE_SIZE).min(self.tiles_y - 1);
+
+        for ty in first_ty..=last_ty {
+            for tx in first_tx..=last_tx {
+                self.tiles[(ty * self.tiles_x + tx) as usize]
+                    .triangles
+                    .push(index);
+            }
+        }
+    }
+}
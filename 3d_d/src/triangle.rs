@@ -1,42 +1,257 @@
 use crate::fragment::Fragment;
-use crate::vertex::Vertex;
+use crate::stats;
+use crate::vertex_output::{Varyings, VertexOutput};
 use raylib::prelude::*;
 
-pub fn triangle(v0: &Vertex, v1: &Vertex, v2: &Vertex) -> Vec<Fragment> {
+// Las posiciones en pantalla se redondean a punto fijo con 4 bits de
+// fracción (1/16 de píxel) para que los bordes se muevan suavemente y las
+// funciones de arista sean exactas
+const SUBPIXEL_BITS: u32 = 4;
+const SUBPIXEL_SCALE: i64 = 1 << SUBPIXEL_BITS;
+const HALF_PIXEL: i64 = SUBPIXEL_SCALE / 2;
+
+#[derive(Clone, Copy)]
+struct FixedPoint {
+    x: i64,
+    y: i64,
+}
+
+impl FixedPoint {
+    fn from_screen(position: Vector3) -> Self {
+        FixedPoint {
+            x: (position.x * SUBPIXEL_SCALE as f32).round() as i64,
+            y: (position.y * SUBPIXEL_SCALE as f32).round() as i64,
+        }
+    }
+}
+
+fn edge_function(a: FixedPoint, b: FixedPoint, p: FixedPoint) -> i64 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+// Regla top-left: un píxel sobre una arista compartida solo pertenece al
+// triángulo si esa arista es superior o izquierda. Con área positiva (Y hacia
+// abajo) una arista superior es horizontal hacia +x y una izquierda sube.
+fn is_top_left(a: FixedPoint, b: FixedPoint) -> bool {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    (dy == 0 && dx > 0) || dy < 0
+}
+
+fn covers(edge_value: i64, top_left: bool) -> bool {
+    edge_value > 0 || (edge_value == 0 && top_left)
+}
+
+// Posiciones de muestra MSAA relativas al centro del píxel, en 1/16 de
+// píxel (patrones estándar de 2x y 4x)
+fn sample_offsets(samples: u32) -> &'static [(i64, i64)] {
+    match samples {
+        2 => &[(4, 4), (-4, -4)],
+        4 => &[(-2, -6), (6, -2), (-6, 2), (2, 6)],
+        _ => &[(0, 0)],
+    }
+}
+
+// Rectángulo de píxeles en pantalla, con límites inclusivos
+#[derive(Clone, Copy, Debug)]
+pub struct ScreenRect {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+}
+
+impl ScreenRect {
+    pub fn new(min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> Self {
+        ScreenRect {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        }
+    }
+
+    pub fn intersect(&self, other: &ScreenRect) -> ScreenRect {
+        ScreenRect::new(
+            self.min_x.max(other.min_x),
+            self.min_y.max(other.min_y),
+            self.max_x.min(other.max_x),
+            self.max_y.min(other.max_y),
+        )
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
+    }
+}
+
+// Rasteriza el triángulo generando solo fragmentos dentro de `rect`
+pub fn triangle_in_rect(
+    v0: &VertexOutput,
+    v1: &VertexOutput,
+    v2: &VertexOutput,
+    perspective_correct: bool,
+    samples: u32,
+    rect: &ScreenRect,
+) -> Vec<Fragment> {
     let mut fragments = Vec::new();
 
-    let x0 = v0.transformed_position.x;
-    let y0 = v0.transformed_position.y;
-    let x1 = v1.transformed_position.x;
-    let y1 = v1.transformed_position.y;
-    let x2 = v2.transformed_position.x;
-    let y2 = v2.transformed_position.y;
+    // Una coordenada NaN o infinita se convertiría en basura al pasar a punto fijo
+    let finite = [v0, v1, v2].iter().all(|v| {
+        let p = v.screen_position;
+        p.x.is_finite() && p.y.is_finite() && p.z.is_finite() && v.clip_position.w.is_finite()
+    });
+    if !finite {
+        stats::count_non_finite_triangle();
+        return fragments;
+    }
+
+    let area = edge_function(
+        FixedPoint::from_screen(v0.screen_position),
+        FixedPoint::from_screen(v1.screen_position),
+        FixedPoint::from_screen(v2.screen_position),
+    );
+    if area == 0 {
+        stats::count_degenerate_triangle();
+        return fragments;
+    }
+
+    // Se ordenan los vértices para que el área sea positiva y la regla
+    // top-left sea la misma sin importar el orden de entrada
+    let (v1, v2) = if area < 0 { (v2, v1) } else { (v1, v2) };
+    let area = area.abs() as f32;
 
-    let min_x = x0.min(x1).min(x2).floor().max(0.0) as i32;
-    let max_x = x0.max(x1).max(x2).ceil() as i32;
-    let min_y = y0.min(y1).min(y2).floor().max(0.0) as i32;
-    let max_y = y0.max(y1).max(y2).ceil() as i32;
+    let p0 = FixedPoint::from_screen(v0.screen_position);
+    let p1 = FixedPoint::from_screen(v1.screen_position);
+    let p2 = FixedPoint::from_screen(v2.screen_position);
 
-    let denom = (y1 - y2) * (x0 - x2) + (x2 - x1) * (y0 - y2);
-    if denom.abs() < f32::EPSILON {
+    let top_left_12 = is_top_left(p1, p2);
+    let top_left_20 = is_top_left(p2, p0);
+    let top_left_01 = is_top_left(p0, p1);
+
+    let to_pixel = |value: i64| value.div_euclid(SUBPIXEL_SCALE) as i32;
+    let min_x = to_pixel(p0.x.min(p1.x).min(p2.x)).max(rect.min_x);
+    let max_x = to_pixel(p0.x.max(p1.x).max(p2.x)).min(rect.max_x);
+    let min_y = to_pixel(p0.y.min(p1.y).min(p2.y)).max(rect.min_y);
+    let max_y = to_pixel(p0.y.max(p1.y).max(p2.y)).min(rect.max_y);
+    if min_x > max_x || min_y > max_y {
         return fragments;
     }
 
+    // Con proyección en perspectiva los atributos no son lineales en pantalla:
+    // se interpola atributo/w y 1/w, y luego se divide.
+    let (inv_w0, inv_w1, inv_w2) = if perspective_correct {
+        (
+            1.0 / v0.clip_position.w,
+            1.0 / v1.clip_position.w,
+            1.0 / v2.clip_position.w,
+        )
+    } else {
+        (1.0, 1.0, 1.0)
+    };
+
+    // Las funciones de arista son lineales: se evalúan una vez en la esquina
+    // del bounding box y luego se avanzan sumando constantes por píxel y fila
+    let start = FixedPoint {
+        x: min_x as i64 * SUBPIXEL_SCALE + HALF_PIXEL,
+        y: min_y as i64 * SUBPIXEL_SCALE + HALF_PIXEL,
+    };
+    let mut row_e12 = edge_function(p1, p2, start);
+    let mut row_e20 = edge_function(p2, p0, start);
+    let mut row_e01 = edge_function(p0, p1, start);
+
+    let (step_x12, step_y12) = (
+        (p1.y - p2.y) * SUBPIXEL_SCALE,
+        (p2.x - p1.x) * SUBPIXEL_SCALE,
+    );
+    let (step_x20, step_y20) = (
+        (p2.y - p0.y) * SUBPIXEL_SCALE,
+        (p0.x - p2.x) * SUBPIXEL_SCALE,
+    );
+    let (step_x01, step_y01) = (
+        (p0.y - p1.y) * SUBPIXEL_SCALE,
+        (p1.x - p0.x) * SUBPIXEL_SCALE,
+    );
+
+    // Desplazamiento de cada función de arista en cada posición de muestra
+    let offsets = sample_offsets(samples);
+    let sample_deltas: Vec<(i64, i64, i64)> = offsets
+        .iter()
+        .map(|&(ox, oy)| {
+            (
+                ox * (p1.y - p2.y) + oy * (p2.x - p1.x),
+                ox * (p2.y - p0.y) + oy * (p0.x - p2.x),
+                ox * (p0.y - p1.y) + oy * (p1.x - p0.x),
+            )
+        })
+        .collect();
+
     for y in min_y..=max_y {
+        let mut e12 = row_e12;
+        let mut e20 = row_e20;
+        let mut e01 = row_e01;
+
         for x in min_x..=max_x {
-            let w0 = ((y1 - y2) * (x as f32 - x2) + (x2 - x1) * (y as f32 - y2)) / denom;
-            let w1 = ((y2 - y0) * (x as f32 - x2) + (x0 - x2) * (y as f32 - y2)) / denom;
-            let w2 = 1.0 - w0 - w1;
-
-            if w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0 {
-                let depth = 0.0;
-                fragments.push(Fragment {
-                    position: Vector2::new(x as f32, y as f32),
-                    depth,
-                    color: Vector3::new(0.3, 0.7, 0.9),
-                });
+            let mut coverage: u8 = 0;
+            for (i, &(d12, d20, d01)) in sample_deltas.iter().enumerate() {
+                if covers(e12 + d12, top_left_12)
+                    && covers(e20 + d20, top_left_20)
+                    && covers(e01 + d01, top_left_01)
+                {
+                    coverage |= 1 << i;
+                }
             }
+
+            // Se sombrea una sola vez por píxel, en el centro, aunque solo
+            // algunas muestras estén cubiertas
+            if coverage != 0 {
+                let w0 = e12 as f32 / area;
+                let w1 = e20 as f32 / area;
+                let w2 = e01 as f32 / area;
+
+                // La profundidad ya está dividida entre w, así que es lineal en pantalla
+                let depth = w0 * v0.screen_position.z
+                    + w1 * v1.screen_position.z
+                    + w2 * v2.screen_position.z;
+
+                let inv_w = w0 * inv_w0 + w1 * inv_w1 + w2 * inv_w2;
+                let pc0 = w0 * inv_w0 / inv_w;
+                let pc1 = w1 * inv_w1 / inv_w;
+                let pc2 = w2 * inv_w2 / inv_w;
+
+                if depth.is_finite() && inv_w.is_finite() && inv_w != 0.0 {
+                    let varyings = Varyings::weighted_sum(
+                        &v0.varyings,
+                        &v1.varyings,
+                        &v2.varyings,
+                        pc0,
+                        pc1,
+                        pc2,
+                    );
+
+                    fragments.push(Fragment {
+                        position: Vector2::new(x as f32, y as f32),
+                        depth,
+                        color: varyings.color,
+                        normal: varyings.normal.normalized(),
+                        tex_coords: varyings.tex_coords,
+                        world_position: varyings.world_position,
+                        object_position: varyings.object_position,
+                        custom: varyings.custom,
+                        coverage,
+                    });
+                } else {
+                    stats::count_non_finite_fragment();
+                }
+            }
+
+            e12 += step_x12;
+            e20 += step_x20;
+            e01 += step_x01;
         }
+
+        row_e12 += step_y12;
+        row_e20 += step_y20;
+        row_e01 += step_y01;
     }
 
     fragments
@@ -0,0 +1,93 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use raylib::prelude::*;
+use std::f32::consts::{FRAC_PI_3, PI};
+
+// Cuánto se alejan del punto de Lagrange, en ángulo alrededor de la estrella
+// y en fracción del radio de la órbita
+const LEAD_SPREAD: f32 = 0.35;
+const RADIAL_SPREAD: f32 = 0.05;
+const VERTICAL_SPREAD: f32 = 0.03;
+
+// Una roca troyana: va 60° adelante (L4) o atrás (L5) del gigante en su
+// misma órbita, desparramada alrededor del punto
+pub struct Trojan {
+    lead: f32,
+    radial: f32,
+    lift: f32,
+    pub scale: f32,
+    tumble: Vector3,
+    tumble_rate: Vector3,
+}
+
+// Los dos enjambres de troyanos de un planeta. Se ubican respecto a dónde
+// están la estrella y el planeta en cada frame, así lo siguen también con la
+// gravedad activa.
+pub struct Trojans {
+    trojans: Vec<Trojan>,
+}
+
+impl Trojans {
+    pub fn generate(seed: u64, count_per_swarm: usize, size: f32) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let turn = 0.0..2.0 * PI;
+        let trojans = [FRAC_PI_3, -FRAC_PI_3]
+            .into_iter()
+            .flat_map(|point| std::iter::repeat_n(point, count_per_swarm))
+            .map(|point| {
+                // Suma de dos uniformes: más densos cerca del punto
+                let mut spread = || rng.gen_range(-0.5..0.5) + rng.gen_range(-0.5..0.5);
+                let (lead, radial, lift) = (spread(), spread(), spread());
+                Trojan {
+                    lead: point + lead * LEAD_SPREAD,
+                    radial: 1.0 + radial * RADIAL_SPREAD,
+                    lift: lift * VERTICAL_SPREAD,
+                    scale: size * rng.gen_range(0.4..1.6),
+                    tumble: Vector3::new(
+                        rng.gen_range(turn.clone()),
+                        rng.gen_range(turn.clone()),
+                        rng.gen_range(turn.clone()),
+                    ),
+                    tumble_rate: Vector3::new(
+                        rng.gen_range(-3.0..3.0),
+                        rng.gen_range(-3.0..3.0),
+                        rng.gen_range(-3.0..3.0),
+                    ),
+                }
+            })
+            .collect();
+        Trojans { trojans }
+    }
+
+    // Cada roca con su posición y su giro en `angle`
+    pub fn rocks(
+        &self,
+        star: Vector3,
+        planet: Vector3,
+        angle: f32,
+    ) -> impl Iterator<Item = (&Trojan, Vector3, Vector3)> {
+        let arm = planet - star;
+        let distance = arm.length();
+        self.trojans.iter().map(move |trojan| {
+            let position = star
+                + rotate_z(arm, trojan.lead) * trojan.radial
+                + Vector3::new(0.0, 0.0, trojan.lift * distance);
+            (trojan, position, trojan.tumble + trojan.tumble_rate * angle)
+        })
+    }
+}
+
+// L4 y L5: los vértices de los dos triángulos equiláteros que forman la
+// estrella y el planeta, en el plano de las órbitas
+pub fn lagrange_points(star: Vector3, planet: Vector3) -> [Vector3; 2] {
+    let arm = planet - star;
+    [
+        star + rotate_z(arm, FRAC_PI_3),
+        star + rotate_z(arm, -FRAC_PI_3),
+    ]
+}
+
+fn rotate_z(v: Vector3, angle: f32) -> Vector3 {
+    let (sin, cos) = angle.sin_cos();
+    Vector3::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos, v.z)
+}
@@ -0,0 +1,74 @@
+use raylib::math::{Vector2, Vector3, Vector4};
+
+pub const MAX_CUSTOM_VARYINGS: usize = 4;
+
+// Valores que el vertex shader entrega al rasterizador; todos se interpolan
+// por fragmento.
+#[derive(Clone, Debug, Default)]
+pub struct Varyings {
+    pub normal: Vector3,
+    pub tex_coords: Vector2,
+    pub world_position: Vector3,
+    // Posición en el espacio del modelo: patrones fijos a la superficie
+    pub object_position: Vector3,
+    pub color: Vector3,
+    pub custom: [f32; MAX_CUSTOM_VARYINGS],
+}
+
+impl Varyings {
+    pub fn lerp(&self, other: &Varyings, t: f32) -> Varyings {
+        Varyings::weighted_sum(self, other, other, 1.0 - t, t, 0.0)
+    }
+
+    // Combinación baricéntrica de los atributos de tres vértices
+    pub fn weighted_sum(
+        a: &Varyings,
+        b: &Varyings,
+        c: &Varyings,
+        w0: f32,
+        w1: f32,
+        w2: f32,
+    ) -> Varyings {
+        let mut custom = [0.0; MAX_CUSTOM_VARYINGS];
+        for (i, value) in custom.iter_mut().enumerate() {
+            *value = a.custom[i] * w0 + b.custom[i] * w1 + c.custom[i] * w2;
+        }
+
+        Varyings {
+            normal: a.normal * w0 + b.normal * w1 + c.normal * w2,
+            tex_coords: a.tex_coords * w0 + b.tex_coords * w1 + c.tex_coords * w2,
+            world_position: a.world_position * w0 + b.world_position * w1 + c.world_position * w2,
+            object_position: a.object_position * w0
+                + b.object_position * w1
+                + c.object_position * w2,
+            color: a.color * w0 + b.color * w1 + c.color * w2,
+            custom,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct VertexOutput {
+    pub clip_position: Vector4,
+    // Coordenadas de pantalla (x, y en píxeles, z en [0, 1]); se llenan tras el recorte
+    pub screen_position: Vector3,
+    pub varyings: Varyings,
+}
+
+impl VertexOutput {
+    pub fn new(clip_position: Vector4, varyings: Varyings) -> Self {
+        VertexOutput {
+            clip_position,
+            screen_position: Vector3::zero(),
+            varyings,
+        }
+    }
+
+    pub fn lerp(&self, other: &VertexOutput, t: f32) -> VertexOutput {
+        VertexOutput {
+            clip_position: self.clip_position.lerp(other.clip_position, t),
+            screen_position: self.screen_position.lerp(other.screen_position, t),
+            varyings: self.varyings.lerp(&other.varyings, t),
+        }
+    }
+}
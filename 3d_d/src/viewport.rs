@@ -0,0 +1,47 @@
+use crate::triangle::ScreenRect;
+
+// Región del framebuffer donde se dibuja la escena, en píxeles internos
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Viewport {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    // El rectángulo más grande con la proporción `aspect` centrado en el
+    // área dada; lo que sobra queda como franjas sin dibujar
+    pub fn letterboxed(area_width: u32, area_height: u32, aspect: f32) -> Self {
+        let area_aspect = area_width as f32 / area_height.max(1) as f32;
+        if area_aspect > aspect {
+            let width = (area_height as f32 * aspect).round() as u32;
+            Viewport::new((area_width - width) / 2, 0, width, area_height)
+        } else {
+            let height = (area_width as f32 / aspect).round() as u32;
+            Viewport::new(0, (area_height - height) / 2, area_width, height)
+        }
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.width as f32 / self.height.max(1) as f32
+    }
+
+    pub fn rect(&self) -> ScreenRect {
+        ScreenRect::new(
+            self.x as i32,
+            self.y as i32,
+            (self.x + self.width) as i32 - 1,
+            (self.y + self.height) as i32 - 1,
+        )
+    }
+}